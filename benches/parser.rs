@@ -0,0 +1,120 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use kraken_sdk::parser::Parser;
+use kraken_sdk::{Decimal, MessageType};
+use serde::Deserialize;
+
+const TICKER: &str = r#"{"channel":"ticker","type":"update","data":[{"symbol":"BTC/USD","bid":42000.1,"bid_qty":0.5,"ask":42000.2,"ask_qty":1.25,"last":42000.1,"volume":1234.5,"vwap":41900.0,"low":41000.0,"high":43000.0,"change":100.0,"change_pct":0.24}]}"#;
+
+const BOOK: &str = r#"{"channel":"book","type":"update","data":[{"symbol":"BTC/USD","bids":[{"price":42000.1,"qty":0.5},{"price":41999.9,"qty":1.2}],"asks":[{"price":42000.2,"qty":0.75}],"checksum":2439117997,"timestamp":"2024-01-01T00:00:00.000000Z"}]}"#;
+
+// The previous untagged layout, kept here as the baseline: every message is
+// tried against each variant in declaration order until one fits. Numbers
+// decode into `Decimal` exactly as the SDK's events do, so both sides do
+// the same work apart from the dispatch.
+#[allow(dead_code)]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Untagged {
+    Ticker(Wrapper<Ticker>),
+    Trade(Wrapper<Trade>),
+    Book(Wrapper<Book>),
+    Heartbeat { channel: String },
+    Unknown(serde_json::Value),
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+struct Wrapper<T> {
+    channel: String,
+    #[serde(rename = "type")]
+    event_type: MessageType,
+    data: Vec<T>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+struct Ticker {
+    symbol: String,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    bid: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    bid_qty: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    ask: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    ask_qty: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    last: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    volume: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    vwap: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    low: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    high: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    change: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    change_pct: Decimal,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+struct Trade {
+    symbol: String,
+    side: String,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    price: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    qty: Decimal,
+    trade_id: u64,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+struct Level {
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    price: Decimal,
+    #[serde(with = "rust_decimal::serde::arbitrary_precision")]
+    qty: Decimal,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+struct Book {
+    symbol: String,
+    bids: Vec<Level>,
+    asks: Vec<Level>,
+    checksum: u32,
+    timestamp: Option<String>,
+}
+
+fn bench_messages(c: &mut Criterion) {
+    for (name, message) in [("ticker", TICKER), ("book", BOOK)] {
+        let mut group = c.benchmark_group(name);
+        group.throughput(Throughput::Bytes(message.len() as u64));
+        group.bench_function("tagged", |b| {
+            b.iter(|| Parser::parse_standard(black_box(message)).unwrap())
+        });
+        group.bench_function("untagged", |b| {
+            b.iter(|| serde_json::from_str::<Untagged>(black_box(message)).unwrap())
+        });
+        #[cfg(feature = "zero-copy")]
+        {
+            let mut parser = kraken_sdk::ZeroCopyParser::default();
+            let mut frame = Vec::with_capacity(message.len());
+            group.bench_function("zero_copy", |b| {
+                b.iter(|| {
+                    frame.clear();
+                    frame.extend_from_slice(message.as_bytes());
+                    black_box(parser.parse(&mut frame).unwrap());
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_messages);
+criterion_main!(benches);
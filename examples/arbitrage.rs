@@ -1,4 +1,5 @@
-use kraken_sdk::{Client, Config};
+use kraken_sdk::{Client, Config, Decimal};
+use rust_decimal_macros::dec;
 use std::collections::HashMap;
 use std::time::Instant;
 
@@ -19,7 +20,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         client.subscribe_ticker().symbol(*symbol).send().await?;
     }
 
-    let mut prices: HashMap<String, f64> = HashMap::new();
+    let mut prices: HashMap<String, Decimal> = HashMap::new();
     let mut opportunities = 0;
     let start = Instant::now();
     let mut count = 0;
@@ -37,9 +38,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 prices.insert(ticker.symbol.clone(), ticker.last);
 
                 // Compare bid/ask spread as proxy for arbitrage opportunity
-                let spread_pct = ((ticker.ask - ticker.bid) / ticker.last) * 100.0;
+                let spread_pct = ((ticker.ask - ticker.bid) / ticker.last) * Decimal::ONE_HUNDRED;
 
-                if spread_pct > 0.5 {
+                if spread_pct > dec!(0.5) {
                     opportunities += 1;
                     println!(
                         " OPPORTUNITY #{}: {} | Spread: {:.2}% | Bid: ${:.2} | Ask: ${:.2}",
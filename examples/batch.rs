@@ -1,4 +1,5 @@
 use kraken_sdk::{AddOrderRequest, BatchOrderRequest, Client, Config};
+use rust_decimal_macros::dec;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -6,16 +7,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut client = Client::from_conf(config);
     let mut _rx = client.connect().await?;
 
-    // Batch multiple orders
+    // Batch multiple orders on one symbol
     let orders = vec![
-        AddOrderRequest::market_buy("BTC/USD", "0.001"),
-        AddOrderRequest::limit_sell("BTC/USD", "0.002", "60000"),
-        AddOrderRequest::limit_sell("ETH/USD", "0.1", "4000"),
+        AddOrderRequest::market_buy("BTC/USD", dec!(0.001)),
+        AddOrderRequest::limit_sell("BTC/USD", dec!(0.002), dec!(60000)),
+        AddOrderRequest::limit_sell("BTC/USD", dec!(0.002), dec!(65000)),
     ];
 
     let batch = BatchOrderRequest::from_requests(orders);
-    client.batch_orders(batch).await?;
-
-    println!("Batch of 3 orders sent successfully");
+    for (leg, result) in client.batch_orders(batch).await?.into_iter().enumerate() {
+        match result {
+            Ok(placed) => println!("Order {} placed: {}", leg + 1, placed.order_id),
+            Err(e) => println!("Order {} rejected: {}", leg + 1, e),
+        }
+    }
     Ok(())
 }
@@ -1,4 +1,5 @@
 use kraken_sdk::{AddOrderRequest, BatchOrderRequest, CancelOrderRequest, Client, Config};
+use rust_decimal_macros::dec;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -10,27 +11,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut _rx = client.connect().await?;
 
     // Single orders
-    let market_order = AddOrderRequest::market_buy("BTC/USD", "0.001");
-    client.add_order(market_order).await?;
-    println!(" Market buy order sent");
+    let market_order = AddOrderRequest::market_buy("BTC/USD", dec!(0.001));
+    let placed = client.add_order(market_order).await?;
+    println!(" Market buy order placed: {}", placed.order_id);
 
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
     // Batch orders
     let orders = vec![
-        AddOrderRequest::limit_sell("BTC/USD", "0.001", "50000"),
-        AddOrderRequest::limit_sell("ETH/USD", "0.1", "3000"),
+        AddOrderRequest::limit_sell("BTC/USD", dec!(0.001), dec!(50000)),
+        AddOrderRequest::limit_sell("BTC/USD", dec!(0.001), dec!(55000)),
     ];
     let batch = BatchOrderRequest::from_requests(orders);
-    client.batch_orders(batch).await?;
-    println!(" Batch orders sent");
+    let placed = client.batch_orders(batch).await?;
+    let accepted = placed.iter().filter(|result| result.is_ok()).count();
+    println!(" Batch orders placed: {}/{}", accepted, placed.len());
 
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
     // Cancel order
     let cancel_order = CancelOrderRequest::new("ORDER_ID_123");
-    client.cancel_order(cancel_order).await?;
-    println!(" Cancel order sent");
+    let cancelled = client.cancel_order(cancel_order).await?;
+    println!(" Orders cancelled: {}", cancelled.len());
 
     Ok(())
 }
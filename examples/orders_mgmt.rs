@@ -1,4 +1,5 @@
 use kraken_sdk::{AddOrderRequest, Auth, BatchOrderRequest, CancelOrderRequest, Client, Config};
+use rust_decimal_macros::dec;
 use std::env;
 
 #[tokio::main]
@@ -16,13 +17,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .build();
 
     let mut client = Client::from_conf(config);
+    // Order requests fail with `Error::NotConnected` until the socket is up.
+    let mut rx = client.connect().await?;
 
     println!(" Authenticated Order Operations:");
     println!("==================================\n");
 
     // 1. Single Order Placement
     println!("1️⃣  Placing single limit order...");
-    let limit_order = AddOrderRequest::limit_buy("BTC/USD", "0.001", "41000");
+    let limit_order = AddOrderRequest::limit_buy("BTC/USD", dec!(0.001), dec!(41000));
 
     match client.add_order(limit_order).await {
         Ok(_) => println!("    Limit buy order placed: 0.001 BTC @ $41,000"),
@@ -33,7 +36,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // 2. Market Order
     println!("\n2️⃣  Placing market order...");
-    let market_order = AddOrderRequest::market_sell("ETH/USD", "0.1");
+    let market_order = AddOrderRequest::market_sell("ETH/USD", dec!(0.1));
 
     match client.add_order(market_order).await {
         Ok(_) => println!("    Market sell order placed: 0.1 ETH"),
@@ -45,14 +48,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 3. Batch Orders
     println!("\n3️⃣  Placing batch orders...");
     let batch_orders = vec![
-        AddOrderRequest::limit_sell("BTC/USD", "0.0005", "43000"),
-        AddOrderRequest::limit_sell("BTC/USD", "0.0005", "44000"),
-        AddOrderRequest::limit_buy("ETH/USD", "0.05", "2400"),
+        AddOrderRequest::limit_sell("BTC/USD", dec!(0.0005), dec!(43000)),
+        AddOrderRequest::limit_sell("BTC/USD", dec!(0.0005), dec!(44000)),
+        AddOrderRequest::limit_buy("BTC/USD", dec!(0.0005), dec!(40000)),
     ];
 
     let batch = BatchOrderRequest::from_requests(batch_orders);
     match client.batch_orders(batch).await {
-        Ok(_) => println!("    Batch orders placed: 3 orders submitted"),
+        Ok(results) => {
+            let placed = results.iter().filter(|result| result.is_ok()).count();
+            println!("    Batch orders placed: {}/{} accepted", placed, results.len());
+        }
         Err(e) => println!("    Batch failed: {}", e),
     }
 
@@ -70,7 +76,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 5. Real-time order status from market events
     println!("\n5️⃣  Real-time market updates:");
 
-    let mut rx = client.connect().await?;
     client.subscribe_ticker().symbol("BTC/USD").send().await?;
 
     let mut update_count = 0;
@@ -1,4 +1,5 @@
 use kraken_sdk::{Auth, Client, Config};
+use rust_decimal_macros::dec;
 use std::env;
 
 #[tokio::main]
@@ -17,30 +18,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("   export KRAKEN_PRIVATE_KEY=your_actual_private_key\n");
     }
 
-    // Create authenticated client
+    // Create authenticated client: tokens are fetched and attached to
+    // private requests automatically, on the authenticated endpoint
     let auth = Auth::new(api_key, private_key);
-    let config = Config::builder()
-        .ws_url("wss://ws-auth.kraken.com/v2")
-        .build();
+    println!(" Authentication Details:");
+    println!("   API Key: {}...", &auth.api_key()[..8]);
 
+    let config = Config::builder().auth(auth).build();
     let mut client = Client::from_conf(config);
 
-    // Generate authentication signature
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)?
-        .as_secs();
-    let nonce = timestamp * 1000;
-    let auth_message = format!("{}POST/ws/v2{}", nonce, timestamp);
-    let signature = auth.sign(&auth_message)?;
-
-    println!(" Authentication Details:");
-    println!("   API Key: {}...", &auth.api_key()[..8]);
-    println!("   Signature: {}...", &signature[..16]);
-    println!("   Nonce: {}", nonce);
     let mut rx = client.connect().await?;
 
     // Subscribe to private channels (requires authentication)
     client.subscribe_ticker().symbol("BTC/USD").send().await?;
+    client.subscribe_executions().send().await?;
 
     println!(" Connected with private API credentials");
     println!(" Streaming authenticated data...\n");
@@ -76,7 +67,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("\n📝 Placing authenticated order...");
 
             use kraken_sdk::AddOrderRequest;
-            let order = AddOrderRequest::limit_buy("BTC/USD", "0.001", "40000");
+            let order = AddOrderRequest::limit_buy("BTC/USD", dec!(0.001), dec!(40000));
 
             match client.add_order(order).await {
                 Ok(_) => println!(" Order placed successfully"),
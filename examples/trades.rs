@@ -1,4 +1,4 @@
-use kraken_sdk::{Client, Config};
+use kraken_sdk::{Client, Config, Decimal};
 use std::time::Instant;
 
 #[tokio::main]
@@ -11,23 +11,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut client = Client::from_conf(config);
     let mut rx = client.connect().await?;
 
-    client.subscribe_ticker().symbol("BTC/USD").send().await?;
+    client
+        .subscribe_trades()
+        .symbol("BTC/USD")
+        .snapshot(false)
+        .send()
+        .await?;
 
     let start = Instant::now();
     let mut count = 0;
-    let mut volume = 0.0;
+    let mut volume = Decimal::ZERO;
 
     while let Some(event) = rx.recv().await {
-        count += 1;
-
-        if let kraken_sdk::KrakenEvent::Ticker(ticker_wrapper) = event {
-            for ticker in ticker_wrapper.data {
+        if let kraken_sdk::KrakenEvent::Trade(trade_wrapper) = event {
+            for trade in trade_wrapper.data {
                 let timestamp = start.elapsed().as_millis();
-                volume += ticker.volume;
+                count += 1;
+                volume += trade.qty;
 
                 println!(
-                    "{:>6}ms | {} | ${:>8.2} | Vol: {:.3} | Change: {:+.2}%",
-                    timestamp, ticker.symbol, ticker.last, ticker.volume, ticker.change_pct
+                    "{:>6}ms | {} | {:<4} | ${:>8.2} | Qty: {:.8} | {}",
+                    timestamp, trade.symbol, trade.side, trade.price, trade.qty, trade.ord_type
                 );
             }
         }
@@ -40,7 +44,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     println!("\n Trades stream demo complete");
-    println!("Total updates: {}", count);
+    println!("Total trades: {}", count);
     println!("Total volume tracked: {:.3}", volume);
 
     Ok(())
@@ -1,12 +1,24 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
 use base64::{engine::general_purpose, Engine as _};
 use hmac::{Hmac, Mac};
-use sha2::Sha256;
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::{Error, Result};
 
-type HmacSha256 = Hmac<Sha256>;
+type HmacSha512 = Hmac<Sha512>;
 
+/// API key credentials for Kraken's private REST endpoints.
+///
+/// Clones share one nonce counter, so requests signed from any of them keep
+/// increasing.
+#[derive(Clone)]
 pub struct Auth {
     api_key: String,
     api_secret: String,
+    last_nonce: Arc<AtomicU64>,
 }
 
 impl Auth {
@@ -14,15 +26,42 @@ impl Auth {
         Self {
             api_key,
             api_secret,
+            last_nonce: Arc::new(AtomicU64::new(0)),
         }
     }
 
-    pub fn sign(&self, message: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let secret = general_purpose::STANDARD.decode(&self.api_secret)?;
-        let mut mac = HmacSha256::new_from_slice(&secret)?;
-        mac.update(message.as_bytes());
-        let signature = mac.finalize().into_bytes();
-        Ok(general_purpose::STANDARD.encode(signature))
+    /// Milliseconds since the epoch, bumped past the previous nonce when two
+    /// requests land in the same millisecond or the clock steps back.
+    pub fn nonce(&self) -> u64 {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+        let previous = self
+            .last_nonce
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+                Some(now.max(last + 1))
+            })
+            .unwrap_or_default();
+        now.max(previous + 1)
+    }
+
+    /// Kraken's `API-Sign`: HMAC-SHA512, keyed with the decoded secret, of the
+    /// URI path followed by SHA256(nonce + POST data). `post_data` is the
+    /// url-encoded body, which must itself contain `nonce`.
+    pub fn sign(&self, path: &str, nonce: u64, post_data: &str) -> Result<String> {
+        let secret = general_purpose::STANDARD
+            .decode(&self.api_secret)
+            .map_err(|e| Error::InvalidSecret(e.to_string()))?;
+
+        let mut digest = Sha256::new();
+        digest.update(nonce.to_string().as_bytes());
+        digest.update(post_data.as_bytes());
+
+        let mut mac = HmacSha512::new_from_slice(&secret).expect("HMAC accepts keys of any length");
+        mac.update(path.as_bytes());
+        mac.update(&digest.finalize());
+        Ok(general_purpose::STANDARD.encode(mac.finalize().into_bytes()))
     }
 
     pub fn api_key(&self) -> &str {
@@ -30,6 +69,14 @@ impl Auth {
     }
 }
 
+impl fmt::Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Auth")
+            .field("api_key", &self.api_key)
+            .finish_non_exhaustive()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -42,9 +89,47 @@ mod tests {
 
     #[test]
     fn test_sign_message() {
-        let auth = Auth::new("key".to_string(), "c2VjcmV0".to_string()); // "secret" in base64
-        let signature = auth.sign("test message").unwrap();
-        assert!(!signature.is_empty());
-        assert!(signature.len() > 10); // Basic signature length check
+        // The worked example from Kraken's REST authentication guide.
+        let auth = Auth::new(
+            "key".to_string(),
+            "kQH5HW/8p1uGOVjbgWA7FunAmGO8lsSUXNsu3eow76sz84Q18fWxnyRzBHCd3pd5nE9qa99HAZtuZuj6F1huXg=="
+                .to_string(),
+        );
+        let signature = auth
+            .sign(
+                "/0/private/AddOrder",
+                1616492376594,
+                "nonce=1616492376594&ordertype=limit&pair=XBTUSD&price=37500&type=buy&volume=1.25",
+            )
+            .unwrap();
+        assert_eq!(
+            signature,
+            "4/dpxb3iT4tp/ZCVEwSnEsLxx0bqyhLpdfOpc6fn7OR8+UClSV5n9E6aSS8MPtnRfp32bAb0nmbRn6H8ndwLUQ=="
+        );
+    }
+
+    #[test]
+    fn test_nonce_is_monotonic_across_clones() {
+        let auth = Auth::new("key".to_string(), "c2VjcmV0".to_string());
+        let clone = auth.clone();
+        let mut last = 0;
+        for _ in 0..1000 {
+            let next = if last % 2 == 0 {
+                auth.nonce()
+            } else {
+                clone.nonce()
+            };
+            assert!(next > last);
+            last = next;
+        }
+    }
+
+    #[test]
+    fn test_invalid_secret() {
+        let auth = Auth::new("key".to_string(), "not base64!".to_string());
+        assert!(matches!(
+            auth.sign("/0/private/Balance", 1, "nonce=1"),
+            Err(Error::InvalidSecret(_))
+        ));
     }
 }
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+pub use crate::protocol::balances::{
+    AssetBalance, BalancesData, BalancesWrapper, LedgerUpdate, WalletBalance,
+};
+
+/// Per-asset balances from the `balances` channel: the snapshot, then each
+/// ledger entry's resulting wallet balance.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BalanceBook {
+    assets: Arc<Mutex<HashMap<String, AssetBalance>>>,
+}
+
+impl BalanceBook {
+    pub(crate) fn apply(&self, message: &BalancesWrapper) {
+        let mut assets = self.assets.lock().unwrap();
+        match &message.data {
+            BalancesData::Snapshot(balances) => {
+                *assets = balances
+                    .iter()
+                    .map(|balance| (balance.asset.clone(), balance.clone()))
+                    .collect();
+            }
+            BalancesData::Update(entries) => {
+                for entry in entries {
+                    let asset = assets
+                        .entry(entry.asset.clone())
+                        .or_insert_with(|| AssetBalance {
+                            asset: entry.asset.clone(),
+                            asset_class: entry.asset_class.clone(),
+                            balance: Default::default(),
+                            wallets: Vec::new(),
+                        });
+                    Self::set_wallet(asset, entry);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, asset: &str) -> Option<AssetBalance> {
+        self.assets.lock().unwrap().get(asset).cloned()
+    }
+
+    pub(crate) fn all(&self) -> HashMap<String, AssetBalance> {
+        self.assets.lock().unwrap().clone()
+    }
+
+    pub(crate) fn clear(&self) {
+        self.assets.lock().unwrap().clear();
+    }
+
+    fn set_wallet(asset: &mut AssetBalance, entry: &LedgerUpdate) {
+        let wallet = asset
+            .wallets
+            .iter_mut()
+            .find(|w| w.wallet_type == entry.wallet_type && w.id == entry.wallet_id);
+        match wallet {
+            Some(wallet) => wallet.balance = entry.balance,
+            None => asset.wallets.push(WalletBalance {
+                wallet_type: entry.wallet_type.clone(),
+                id: entry.wallet_id.clone(),
+                balance: entry.balance,
+            }),
+        }
+        asset.balance = asset.wallets.iter().map(|w| w.balance).sum();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_ledger_updates_move_wallet_balances() {
+        let book = BalanceBook::default();
+        let snapshot: BalancesWrapper = serde_json::from_str(
+            r#"{"channel":"balances","type":"snapshot","sequence":1,"data":[
+                {"asset":"BTC","asset_class":"currency","balance":1.5,"wallets":[
+                    {"type":"spot","id":"main","balance":1.0},
+                    {"type":"earn","id":"flex","balance":0.5}]}]}"#,
+        )
+        .unwrap();
+        book.apply(&snapshot);
+
+        let update: BalancesWrapper = serde_json::from_str(
+            r#"{"channel":"balances","type":"update","sequence":2,"data":[
+                {"ledger_id":"L1","ref_id":"T1","timestamp":"2024-01-01T00:00:00Z",
+                 "type":"trade","subtype":"","asset":"BTC","asset_class":"currency",
+                 "wallet_type":"spot","wallet_id":"main","amount":-0.25,"fee":0.0005,
+                 "balance":0.7495},
+                {"ledger_id":"L2","ref_id":"T1","timestamp":"2024-01-01T00:00:00Z",
+                 "type":"trade","subtype":"","asset":"USD","asset_class":"currency",
+                 "wallet_type":"spot","wallet_id":"main","amount":10500.0,"fee":0.0,
+                 "balance":10500.0}]}"#,
+        )
+        .unwrap();
+        book.apply(&update);
+
+        assert_eq!(book.get("BTC").unwrap().balance, dec!(1.2495));
+        assert_eq!(book.get("USD").unwrap().balance, dec!(10500.0));
+        assert_eq!(book.all().len(), 2);
+    }
+}
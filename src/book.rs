@@ -0,0 +1,462 @@
+use rust_decimal::Decimal;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::instrument::InstrumentRegistry;
+use crate::protocol::{
+    event::MessageType,
+    trades::{OrderbookEvent, OrderbookWrapper, PriceLevel},
+};
+
+/// Depth Kraken uses when a `book` subscription does not ask for one.
+pub const DEFAULT_DEPTH: u32 = 10;
+
+/// Levels per side covered by Kraken's book checksum.
+const CHECKSUM_LEVELS: usize = 10;
+
+/// Resubscriptions a book gets after failing its checksum, in a row,
+/// before the client gives up on it.
+pub const MAX_BOOK_RESYNCS: u32 = 5;
+
+/// Wait before a book's second resync in a row; each further one waits
+/// twice as long.
+const RESYNC_BACKOFF: Duration = Duration::from_secs(1);
+
+/// A book that has not failed for this long starts counting afresh.
+const RESYNC_RESET: Duration = Duration::from_secs(300);
+
+/// Decimal places Kraken formats a symbol's prices and quantities with.
+/// The book checksum is computed over those exact digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Precision {
+    pub price: u32,
+    pub qty: u32,
+}
+
+impl Precision {
+    /// The precision a message's levels were written with. Kraken pads every
+    /// number to the instrument's decimals, so all prices share one scale
+    /// and all quantities another; `None` when they don't, or when there
+    /// are no levels to tell.
+    pub fn of(data: &OrderbookEvent) -> Option<Precision> {
+        fn shared(mut scales: impl Iterator<Item = u32>) -> Option<u32> {
+            let first = scales.next()?;
+            scales.all(|scale| scale == first).then_some(first)
+        }
+
+        let levels = || data.bids.iter().chain(&data.asks);
+        Some(Precision {
+            price: shared(levels().map(|level| level.price.scale()))?,
+            qty: shared(levels().map(|level| level.qty.scale()))?,
+        })
+    }
+}
+
+/// Emitted as `KrakenEvent::BookResync` when a book fails its checksum. The
+/// local book is discarded and the symbol re-subscribed for a new snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookResync {
+    pub symbol: String,
+    pub expected: u32,
+    pub computed: u32,
+}
+
+/// Local level 2 book for one symbol, kept in sync from `book` messages.
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    symbol: String,
+    depth: u32,
+    precision: Option<Precision>,
+    bids: BTreeMap<Reverse<Decimal>, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+}
+
+impl OrderBook {
+    pub fn new(symbol: impl Into<String>, depth: u32) -> Self {
+        Self {
+            symbol: symbol.into(),
+            depth,
+            precision: None,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+        }
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    pub fn precision(&self) -> Option<Precision> {
+        self.precision
+    }
+
+    pub fn with_precision(mut self, precision: Precision) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// A snapshot replaces the book; an update sets each level's quantity,
+    /// removing it at zero. Levels pushed past the subscribed depth are
+    /// dropped, as Kraken expects.
+    pub fn apply(&mut self, kind: MessageType, data: &OrderbookEvent) {
+        if kind == MessageType::Snapshot {
+            self.bids.clear();
+            self.asks.clear();
+        }
+        for level in &data.bids {
+            Self::set(&mut self.bids, Reverse(level.price), level.qty);
+        }
+        for level in &data.asks {
+            Self::set(&mut self.asks, level.price, level.qty);
+        }
+
+        let depth = self.depth as usize;
+        while self.bids.len() > depth {
+            self.bids.pop_last();
+        }
+        while self.asks.len() > depth {
+            self.asks.pop_last();
+        }
+    }
+
+    pub fn best_bid(&self) -> Option<PriceLevel> {
+        self.bids().next()
+    }
+
+    pub fn best_ask(&self) -> Option<PriceLevel> {
+        self.asks().next()
+    }
+
+    pub fn spread(&self) -> Option<Decimal> {
+        Some(self.best_ask()?.price - self.best_bid()?.price)
+    }
+
+    pub fn mid_price(&self) -> Option<Decimal> {
+        Some((self.best_ask()?.price + self.best_bid()?.price) / Decimal::TWO)
+    }
+
+    /// Every bid, best (highest) first.
+    pub fn bids(&self) -> impl Iterator<Item = PriceLevel> + '_ {
+        self.bids.iter().map(|(Reverse(price), qty)| PriceLevel {
+            price: *price,
+            qty: *qty,
+        })
+    }
+
+    /// Every ask, best (lowest) first.
+    pub fn asks(&self) -> impl Iterator<Item = PriceLevel> + '_ {
+        self.asks.iter().map(|(price, qty)| PriceLevel {
+            price: *price,
+            qty: *qty,
+        })
+    }
+
+    pub fn top_bids(&self, n: usize) -> Vec<PriceLevel> {
+        self.bids().take(n).collect()
+    }
+
+    pub fn top_asks(&self, n: usize) -> Vec<PriceLevel> {
+        self.asks().take(n).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+
+    /// Kraken's CRC32 over the top ten asks then the top ten bids, each
+    /// price and quantity written with the symbol's precision, without the
+    /// decimal point or leading zeros. `None` while the book has levels but
+    /// no known precision.
+    pub fn checksum(&self) -> Option<u32> {
+        let precision = match self.precision {
+            Some(precision) => precision,
+            // An empty book has no digits to format.
+            None if self.is_empty() => Precision { price: 0, qty: 0 },
+            None => return None,
+        };
+        let mut hasher = crc32fast::Hasher::new();
+        let mut digits = String::new();
+        let asks = self.asks().take(CHECKSUM_LEVELS);
+        for level in asks.chain(self.bids().take(CHECKSUM_LEVELS)) {
+            for (value, scale) in [(level.price, precision.price), (level.qty, precision.qty)] {
+                digits.clear();
+                let mut value = value;
+                value.rescale(scale);
+                digits.extend(value.to_string().chars().filter(|c| *c != '.'));
+                hasher.update(digits.trim_start_matches('0').as_bytes());
+            }
+        }
+        Some(hasher.finalize())
+    }
+
+    fn set<K: Ord>(side: &mut BTreeMap<K, Decimal>, price: K, qty: Decimal) {
+        if qty.is_zero() {
+            side.remove(&price);
+        } else {
+            side.insert(price, qty);
+        }
+    }
+}
+
+/// Why `BookStore::apply` dropped a book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum BookFault {
+    /// The book failed its checksum and needs a fresh snapshot.
+    Mismatch(BookResync),
+    /// The book has levels but its precision is unknown, so its checksum
+    /// cannot be verified.
+    UnknownPrecision(String),
+}
+
+/// Paces the resubscriptions of books that fail their checksum, so one
+/// that keeps failing cannot flood Kraken with requests.
+#[derive(Debug, Default)]
+pub(crate) struct ResyncLimiter {
+    recent: HashMap<String, (u32, Instant)>,
+}
+
+impl ResyncLimiter {
+    /// How long to wait before resubscribing `symbol` after a failure at
+    /// `now`: not at all the first time, then with exponential backoff.
+    /// `None` once it has failed `MAX_BOOK_RESYNCS` times in a row.
+    pub(crate) fn next_delay(&mut self, symbol: &str, now: Instant) -> Option<Duration> {
+        let (failures, last) = self.recent.entry(symbol.to_string()).or_insert((0, now));
+        if now.duration_since(*last) >= RESYNC_RESET {
+            *failures = 0;
+        }
+        *failures += 1;
+        *last = now;
+        match *failures {
+            1 => Some(Duration::ZERO),
+            n if n <= MAX_BOOK_RESYNCS => Some(RESYNC_BACKOFF * 2u32.pow(n - 2)),
+            _ => None,
+        }
+    }
+}
+
+/// Books for every `book` subscription, updated by the session before each
+/// message is forwarded so readers never see a book behind the stream.
+#[derive(Debug, Clone)]
+pub(crate) struct BookStore {
+    books: Arc<Mutex<HashMap<String, OrderBook>>>,
+    instruments: InstrumentRegistry,
+}
+
+impl BookStore {
+    pub(crate) fn new(instruments: InstrumentRegistry) -> Self {
+        Self {
+            books: Arc::default(),
+            instruments,
+        }
+    }
+
+    /// Updates for a symbol without a snapshot yet are ignored. A book's
+    /// precision comes from the instrument registry, or else from the
+    /// digits of the first levels it receives. Every message is verified:
+    /// books that fail their checksum, or whose precision cannot be told,
+    /// are dropped and reported.
+    pub(crate) fn apply(
+        &self,
+        message: &OrderbookWrapper,
+        depth_of: impl Fn(&str) -> u32,
+    ) -> Vec<BookFault> {
+        let mut books = self.books.lock().unwrap();
+        let mut faults = Vec::new();
+        for data in &message.data {
+            if message.event_type == MessageType::Snapshot {
+                let mut book = OrderBook::new(data.symbol.clone(), depth_of(&data.symbol));
+                book.precision = self.instruments.precision(&data.symbol);
+                books.insert(data.symbol.clone(), book);
+            }
+            let Some(book) = books.get_mut(&data.symbol) else {
+                continue;
+            };
+            book.apply(message.event_type, data);
+            if book.precision.is_none() {
+                book.precision = Precision::of(data);
+            }
+            let fault = match book.checksum() {
+                Some(computed) if computed == data.checksum => continue,
+                Some(computed) => BookFault::Mismatch(BookResync {
+                    symbol: data.symbol.clone(),
+                    expected: data.checksum,
+                    computed,
+                }),
+                None => BookFault::UnknownPrecision(data.symbol.clone()),
+            };
+            books.remove(&data.symbol);
+            faults.push(fault);
+        }
+        faults
+    }
+
+    pub(crate) fn get(&self, symbol: &str) -> Option<OrderBook> {
+        self.books.lock().unwrap().get(symbol).cloned()
+    }
+
+    pub(crate) fn remove(&self, symbol: &str) {
+        self.books.lock().unwrap().remove(symbol);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.books.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_resyncs_back_off_and_give_up() {
+        let mut limiter = ResyncLimiter::default();
+        let start = Instant::now();
+        let delays: Vec<_> = (0..6)
+            .map(|i| limiter.next_delay("BTC/USD", start + Duration::from_secs(i)))
+            .collect();
+        let secs = Duration::from_secs;
+        assert_eq!(
+            delays,
+            [
+                Some(Duration::ZERO),
+                Some(secs(1)),
+                Some(secs(2)),
+                Some(secs(4)),
+                Some(secs(8)),
+                None
+            ]
+        );
+
+        // Other books are counted on their own, and a quiet book starts over.
+        assert_eq!(limiter.next_delay("ETH/USD", start), Some(Duration::ZERO));
+        let later = start + RESYNC_RESET * 2;
+        assert_eq!(limiter.next_delay("BTC/USD", later), Some(Duration::ZERO));
+    }
+
+    fn levels(pairs: &[(Decimal, Decimal)]) -> Vec<PriceLevel> {
+        pairs
+            .iter()
+            .map(|&(price, qty)| PriceLevel { price, qty })
+            .collect()
+    }
+
+    fn data(bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) -> OrderbookEvent {
+        OrderbookEvent {
+            symbol: "BTC/USD".to_string(),
+            bids: levels(bids),
+            asks: levels(asks),
+            checksum: 0,
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_then_updates() {
+        let mut book = OrderBook::new("BTC/USD", 10);
+        book.apply(
+            MessageType::Snapshot,
+            &data(
+                &[(dec!(100.0), dec!(1)), (dec!(99.5), dec!(2))],
+                &[(dec!(100.5), dec!(3)), (dec!(101.0), dec!(4))],
+            ),
+        );
+        assert_eq!(book.best_bid().unwrap().price, dec!(100.0));
+        assert_eq!(book.best_ask().unwrap().price, dec!(100.5));
+        assert_eq!(book.spread(), Some(dec!(0.5)));
+
+        // Remove the best bid, add a better ask.
+        book.apply(
+            MessageType::Update,
+            &data(&[(dec!(100.0), dec!(0))], &[(dec!(100.2), dec!(5))]),
+        );
+        assert_eq!(book.best_bid().unwrap().price, dec!(99.5));
+        assert_eq!(
+            book.top_asks(2),
+            levels(&[(dec!(100.2), dec!(5)), (dec!(100.5), dec!(3))])
+        );
+    }
+
+    #[test]
+    fn test_levels_beyond_depth_are_dropped() {
+        let mut book = OrderBook::new("BTC/USD", 2);
+        book.apply(
+            MessageType::Snapshot,
+            &data(&[(dec!(100), dec!(1)), (dec!(99), dec!(1))], &[]),
+        );
+        book.apply(MessageType::Update, &data(&[(dec!(99.5), dec!(1))], &[]));
+        let prices: Vec<_> = book.bids().map(|level| level.price).collect();
+        assert_eq!(prices, vec![dec!(100), dec!(99.5)]);
+    }
+
+    fn eth_btc(bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) -> OrderbookEvent {
+        OrderbookEvent {
+            symbol: "ETH/BTC".to_string(),
+            ..data(bids, asks)
+        }
+    }
+
+    #[test]
+    fn test_checksum_uses_snapshot_precision() {
+        let snapshot = eth_btc(
+            &[(dec!(0.05000), dec!(1.50000000))],
+            &[(dec!(0.05005), dec!(0.00000500))],
+        );
+        let precision = Precision::of(&snapshot).unwrap();
+        assert_eq!(precision, Precision { price: 5, qty: 8 });
+
+        let mut book = OrderBook::new("ETH/BTC", 10).with_precision(precision);
+        book.apply(MessageType::Snapshot, &snapshot);
+        // Asks first, each number without its point or leading zeros.
+        assert_eq!(
+            book.checksum(),
+            Some(crc32fast::hash(b"50055005000150000000"))
+        );
+    }
+
+    #[test]
+    fn test_book_without_precision_is_dropped() {
+        let store = BookStore::new(InstrumentRegistry::default());
+        // Unpadded numbers: the scales disagree, so the precision is unknown.
+        let snapshot = OrderbookWrapper {
+            channel: "book".to_string(),
+            event_type: MessageType::Snapshot,
+            data: vec![eth_btc(
+                &[(dec!(0.05), dec!(1.5))],
+                &[(dec!(0.05005), dec!(0.000005))],
+            )],
+        };
+        assert_eq!(
+            store.apply(&snapshot, |_| 10),
+            vec![BookFault::UnknownPrecision("ETH/BTC".to_string())]
+        );
+        assert!(store.get("ETH/BTC").is_none());
+
+        // An empty snapshot verifies, and later levels set the precision.
+        let mut empty = snapshot;
+        empty.data[0] = OrderbookEvent {
+            checksum: crc32fast::hash(b""),
+            ..eth_btc(&[], &[])
+        };
+        assert!(store.apply(&empty, |_| 10).is_empty());
+        let update = OrderbookWrapper {
+            event_type: MessageType::Update,
+            data: vec![OrderbookEvent {
+                checksum: crc32fast::hash(b"5000150000000"),
+                ..eth_btc(&[(dec!(0.05000), dec!(1.50000000))], &[])
+            }],
+            ..empty
+        };
+        assert!(store.apply(&update, |_| 10).is_empty());
+        assert_eq!(
+            store.get("ETH/BTC").unwrap().precision(),
+            Some(Precision { price: 5, qty: 8 })
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use crate::protocol::{
+    event::MessageType,
+    ohlc::{OhlcEvent, OhlcWrapper},
+};
+
+/// Remembers the candle in progress per symbol and interval, to tell when
+/// it has closed.
+#[derive(Debug, Default)]
+pub(crate) struct CandleTracker {
+    open: HashMap<(String, u32), OhlcEvent>,
+}
+
+impl CandleTracker {
+    /// Returns the candles `message` closes: those replaced by a candle
+    /// with a later `interval_begin`. Snapshots only reset the state, since
+    /// the candles they carry before the latest one are history.
+    pub(crate) fn update(&mut self, message: &OhlcWrapper) -> Vec<OhlcEvent> {
+        let mut closed = Vec::new();
+        for candle in &message.data {
+            let key = (candle.symbol.clone(), candle.interval);
+            match self.open.get(&key) {
+                Some(current) if current.interval_begin > candle.interval_begin => continue,
+                Some(current)
+                    if message.event_type == MessageType::Update
+                        && current.interval_begin < candle.interval_begin =>
+                {
+                    closed.push(current.clone());
+                }
+                _ => {}
+            }
+            self.open.insert(key, candle.clone());
+        }
+        closed
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.open.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn message(kind: MessageType, candles: &[(&str, u64)]) -> OhlcWrapper {
+        OhlcWrapper {
+            channel: "ohlc".to_string(),
+            event_type: kind,
+            data: candles
+                .iter()
+                .map(|&(begin, trades)| OhlcEvent {
+                    symbol: "BTC/USD".to_string(),
+                    open: dec!(42000),
+                    high: dec!(42010),
+                    low: dec!(41990),
+                    close: dec!(42005),
+                    vwap: dec!(42001),
+                    trades,
+                    volume: dec!(1.5),
+                    interval_begin: begin.to_string(),
+                    interval: 1,
+                    timestamp: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_candle_closes_when_next_interval_begins() {
+        let mut tracker = CandleTracker::default();
+        let snapshot = message(
+            MessageType::Snapshot,
+            &[("2024-01-01T00:00:00Z", 5), ("2024-01-01T00:01:00Z", 1)],
+        );
+        assert!(tracker.update(&snapshot).is_empty());
+
+        let progress = message(MessageType::Update, &[("2024-01-01T00:01:00Z", 2)]);
+        assert!(tracker.update(&progress).is_empty());
+
+        let next = message(MessageType::Update, &[("2024-01-01T00:02:00Z", 1)]);
+        let closed = tracker.update(&next);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].interval_begin, "2024-01-01T00:01:00Z");
+        assert_eq!(closed[0].trades, 2);
+    }
+}
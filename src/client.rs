@@ -1,47 +1,133 @@
-#[cfg(not(target_arch = "wasm32"))]
-use futures_util::{SinkExt, StreamExt};
-use tokio::sync::mpsc;
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, mpsc};
+#[cfg(target_arch = "wasm32")]
 use tokio::time::sleep;
-#[cfg(not(target_arch = "wasm32"))]
-use tokio_tungstenite::{connect_async, tungstenite::Message};
-#[cfg(not(target_arch = "wasm32"))]
-use url::Url;
 
 #[cfg(target_arch = "wasm32")]
 use crate::wasm_ws::WasmWebSocket;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::connection::{self, Session, Sink};
+#[cfg(all(feature = "zero-copy", not(target_arch = "wasm32")))]
+use crate::stream::RawStream;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::DeadManSwitch;
 use crate::{
-    operation::SubscribeTickerFluentBuilder,
-    protocol::event::KrakenEvent,
+    balances::{AssetBalance, BalanceBook},
+    book::{BookStore, OrderBook},
+    config::{AUTH_WS_URL, LEVEL3_WS_URL, PUBLIC_WS_URL},
+    executions::{ExecutionReport, OpenOrders},
+    instrument::InstrumentRegistry,
+    level3::{Level3Book, Level3Store},
+    operation::{
+        SubscribeBalancesFluentBuilder, SubscribeBookFluentBuilder,
+        SubscribeExecutionsFluentBuilder, SubscribeInstrumentsFluentBuilder,
+        SubscribeLevel3FluentBuilder, SubscribeOhlcFluentBuilder, SubscribeTickerFluentBuilder,
+        SubscribeTradesFluentBuilder, UnsubscribeFluentBuilder,
+    },
+    parser::DecodeError,
+    protocol::{event::KrakenEvent, response::MethodResponse},
+    registry::SubscriptionRegistry,
+    request::{is_token_error, PrivateRequest, RequestTracker},
+    state::{ConnectionState, StateTracker},
     stream::KrakenStream,
-    types::{AddOrderRequest, BatchOrderRequest, CancelOrderRequest, SubscribeInput},
-    Config, Error, Result,
+    types::{
+        AddOrderRequest, AddOrderResult, AmendOrderRequest, AmendOrderResult, BatchCancelRequest,
+        BatchCancelResult, BatchOrderRequest, CancelAllOrdersAfterRequest,
+        CancelAllOrdersAfterResult, CancelAllRequest, CancelAllResult, CancelOrderRequest,
+        CancelOrderResult, Channel, EditOrderRequest, EditOrderResult, SubscribeInput,
+        SubscribeResult, Subscription,
+    },
+    Config, Error, Metrics, Result,
 };
 
-#[cfg(not(target_arch = "wasm32"))]
-use crate::parser::Parser;
+/// State shared by every clone of a `Client` and its session task.
+#[derive(Debug, Clone)]
+pub(crate) struct Shared {
+    pub(crate) subscriptions: SubscriptionRegistry,
+    pub(crate) books: BookStore,
+    pub(crate) instruments: InstrumentRegistry,
+    pub(crate) level3: Level3Store,
+    pub(crate) open_orders: OpenOrders,
+    pub(crate) balances: BalanceBook,
+    pub(crate) state: StateTracker,
+    pub(crate) metrics: Metrics,
+    pub(crate) requests: RequestTracker,
+    pub(crate) decode_errors: broadcast::Sender<DecodeError>,
+}
+
+impl Shared {
+    fn new() -> Self {
+        let (decode_errors, _) = broadcast::channel(64);
+        let instruments = InstrumentRegistry::default();
+        Self {
+            subscriptions: SubscriptionRegistry::default(),
+            books: BookStore::new(instruments.clone()),
+            instruments,
+            level3: Level3Store::default(),
+            open_orders: OpenOrders::default(),
+            balances: BalanceBook::default(),
+            state: StateTracker::new(),
+            metrics: Metrics::default(),
+            requests: RequestTracker::default(),
+            decode_errors,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Client {
     config: Config,
-    cmd_tx: Option<mpsc::Sender<String>>,
+    // Shared so clones taken before `connect` still reach the live session.
+    cmd_tx: Arc<Mutex<Option<mpsc::Sender<String>>>>,
+    shared: Shared,
 }
 
 impl Client {
     pub fn new(config: &Config) -> Self {
-        Self {
-            config: config.clone(),
-            cmd_tx: None,
-        }
+        Self::from_conf(config.clone())
     }
 
     pub fn from_conf(config: Config) -> Self {
         Self {
             config,
-            cmd_tx: None,
+            cmd_tx: Arc::new(Mutex::new(None)),
+            shared: Shared::new(),
         }
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn connect(&mut self) -> Result<mpsc::Receiver<KrakenEvent>> {
+        let (event_tx, event_rx) = mpsc::channel::<KrakenEvent>(100);
+        self.start(Sink::Events(event_tx)).await?;
+        Ok(event_rx)
+    }
+
+    /// Like `stream`, but hands out undecoded frames that `RawStream`
+    /// parses in place into borrowed events.
+    #[cfg(all(feature = "zero-copy", not(target_arch = "wasm32")))]
+    pub async fn raw_stream(&mut self) -> Result<RawStream> {
+        let (frame_tx, frame_rx) = mpsc::channel::<Vec<u8>>(100);
+        self.start(Sink::Frames(frame_tx)).await?;
+        Ok(RawStream::new(frame_rx, self.shared.metrics.clone()))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn start(&mut self, sink: Sink) -> Result<()> {
+        let mut ws = connection::open_with_retry(&self.config, &self.shared.state, false).await?;
+
+        let (cmd_tx, cmd_rx) = mpsc::channel::<String>(32);
+        *self.cmd_tx.lock().unwrap() = Some(cmd_tx);
+
+        let session = Session::new(self.config.clone(), cmd_rx, sink, self.shared.clone());
+        session.resume(&mut ws).await?;
+        tokio::spawn(session.run(ws));
+        Ok(())
+    }
+
+    #[cfg(target_arch = "wasm32")]
     pub async fn connect(&mut self) -> Result<mpsc::Receiver<KrakenEvent>> {
         let mut attempt = 0;
         let mut backoff = self.config.initial_backoff();
@@ -63,51 +149,24 @@ impl Client {
 
     pub async fn stream(&mut self) -> Result<KrakenStream> {
         let rx = self.connect().await?;
-        Ok(KrakenStream::new(rx))
+        Ok(KrakenStream::new(rx, self.shared.metrics.clone()))
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    async fn try_connect(&mut self) -> Result<mpsc::Receiver<KrakenEvent>> {
-        let url = Url::parse(self.config.ws_url())?;
-        let (ws_stream, _) = connect_async(url)
-            .await
-            .map_err(|e| Error::WebSocket(Box::new(e)))?;
-        let (mut write, mut read) = ws_stream.split();
-
-        let (cmd_tx, mut cmd_rx) = mpsc::channel::<String>(32);
-        let (event_tx, event_rx) = mpsc::channel::<KrakenEvent>(100);
-
-        self.cmd_tx = Some(cmd_tx);
+    /// Lifecycle events for the underlying socket. Subscribe before calling
+    /// `connect` to observe the initial `Connecting` transition.
+    pub fn connection_events(&self) -> broadcast::Receiver<ConnectionState> {
+        self.shared.state.subscribe()
+    }
 
-        tokio::spawn(async move {
-            while let Some(msg) = cmd_rx.recv().await {
-                let _ = write.send(Message::Text(msg)).await;
-            }
-        });
-
-        tokio::spawn(async move {
-            while let Some(msg) = read.next().await {
-                if let Ok(msg) = msg {
-                    if msg.is_text() {
-                        let text = msg.to_text().unwrap();
-                        if let Ok(event) = Parser::parse_standard(text) {
-                            if event_tx.send(event).await.is_err() {
-                                break; // Backpressure: receiver dropped
-                            }
-                        }
-                    } else if msg.is_binary() {
-                        let mut data = msg.into_data();
-                        if let Ok(event) = Parser::parse_zero_copy(&mut data) {
-                            if event_tx.send(event).await.is_err() {
-                                break; // Backpressure: receiver dropped
-                            }
-                        }
-                    }
-                }
-            }
-        });
+    pub fn connection_state(&self) -> ConnectionState {
+        self.shared.state.current()
+    }
 
-        Ok(event_rx)
+    /// Messages that could not be decoded, with the raw payload, so schema
+    /// changes on Kraken's side are visible instead of silently dropped.
+    /// `Metrics::decode_error_count` keeps the running total.
+    pub fn decode_errors(&self) -> broadcast::Receiver<DecodeError> {
+        self.shared.decode_errors.subscribe()
     }
 
     #[cfg(target_arch = "wasm32")]
@@ -127,35 +186,350 @@ impl Client {
         SubscribeTickerFluentBuilder::new(self.clone())
     }
 
-    pub(crate) async fn send_subscription(&self, input: SubscribeInput) -> Result<()> {
-        if let Some(tx) = &self.cmd_tx {
-            let msg = serde_json::to_string(&input).map_err(|e| Error::Json(Box::new(e)))?;
-            tx.send(msg).await.map_err(|_| Error::ConnectionClosed)?;
+    pub fn subscribe_trades(&self) -> SubscribeTradesFluentBuilder {
+        SubscribeTradesFluentBuilder::new(self.clone())
+    }
+
+    pub fn subscribe_ohlc(&self) -> SubscribeOhlcFluentBuilder {
+        SubscribeOhlcFluentBuilder::new(self.clone())
+    }
+
+    pub fn subscribe_instruments(&self) -> SubscribeInstrumentsFluentBuilder {
+        SubscribeInstrumentsFluentBuilder::new(self.clone())
+    }
+
+    pub fn subscribe_level3(&self) -> SubscribeLevel3FluentBuilder {
+        SubscribeLevel3FluentBuilder::new(self.clone())
+    }
+
+    pub fn subscribe_executions(&self) -> SubscribeExecutionsFluentBuilder {
+        SubscribeExecutionsFluentBuilder::new(self.clone())
+    }
+
+    pub fn subscribe_balances(&self) -> SubscribeBalancesFluentBuilder {
+        SubscribeBalancesFluentBuilder::new(self.clone())
+    }
+
+    pub fn subscribe_book(&self) -> SubscribeBookFluentBuilder {
+        SubscribeBookFluentBuilder::new(self.clone())
+    }
+
+    pub fn unsubscribe(&self, channel: Channel) -> UnsubscribeFluentBuilder {
+        UnsubscribeFluentBuilder::new(self.clone(), channel)
+    }
+
+    /// Subscriptions currently active on this client, one entry per channel and symbol.
+    pub fn subscriptions(&self) -> Vec<Subscription> {
+        self.shared.subscriptions.list()
+    }
+
+    /// The local book for `symbol`, as of the last `book` message delivered
+    /// to the stream. Maintained for `book` subscriptions when events are
+    /// read through `connect` or `stream`.
+    pub fn order_book(&self, symbol: &str) -> Option<OrderBook> {
+        self.shared.books.get(symbol)
+    }
+
+    /// The local level 3 book for `symbol`, maintained like `order_book`
+    /// for `level3` subscriptions.
+    pub fn level3_book(&self, symbol: &str) -> Option<Level3Book> {
+        self.shared.level3.get(symbol)
+    }
+
+    /// Open orders from the `executions` channel, merged from the snapshot
+    /// and every update since. Closed orders are dropped.
+    pub fn open_orders(&self) -> Vec<ExecutionReport> {
+        self.shared.open_orders.list()
+    }
+
+    pub fn open_order(&self, order_id: &str) -> Option<ExecutionReport> {
+        self.shared.open_orders.get(order_id)
+    }
+
+    /// Balances per asset from the `balances` channel, updated with every
+    /// ledger entry.
+    pub fn balances(&self) -> HashMap<String, AssetBalance> {
+        self.shared.balances.all()
+    }
+
+    pub fn balance(&self, asset: &str) -> Option<AssetBalance> {
+        self.shared.balances.get(asset)
+    }
+
+    /// Pair and asset reference data, filled in by `subscribe_instruments`.
+    /// The handle stays current as updates arrive.
+    pub fn instruments(&self) -> InstrumentRegistry {
+        self.shared.instruments.clone()
+    }
+
+    /// Subscriptions made while the socket is down are queued in the
+    /// registry and sent by the session as soon as it (re)connects; they
+    /// resolve immediately with no acknowledgements.
+    pub(crate) async fn send_subscription(
+        &self,
+        input: SubscribeInput,
+    ) -> Result<Vec<SubscribeResult>> {
+        self.check_token(&input)?;
+        // Kraken's other endpoints would only answer with an unhelpful error.
+        let url = self.config.ws_url();
+        if input.params.channel == Channel::Level3 && [PUBLIC_WS_URL, AUTH_WS_URL].contains(&url) {
+            return Err(Error::WrongEndpoint {
+                channel: input.params.channel.to_string(),
+                url: url.to_string(),
+                expected: LEVEL3_WS_URL.to_string(),
+            });
         }
-        Ok(())
+        let Some(input) = self.shared.subscriptions.subscribe(input) else {
+            return Ok(Vec::new()); // Already subscribed, or queued until connected
+        };
+
+        let responses = self.send_channel_request(input.clone()).await?;
+        let failed: Vec<&MethodResponse> = responses.iter().filter(|r| !r.success).collect();
+        if !failed.is_empty() {
+            // Rejected by the exchange: don't replay it after a reconnect.
+            // Kraken names the symbol it refused; other symbols were acked
+            // and keep streaming.
+            let mut rejected = input;
+            if failed.iter().all(|r| r.symbol.is_some()) {
+                rejected.params.symbol = failed.iter().filter_map(|r| r.symbol.clone()).collect();
+            }
+            self.shared.subscriptions.unsubscribe(rejected);
+        }
+        MethodResponse::into_results(responses)
     }
 
-    pub async fn add_order(&self, order: AddOrderRequest) -> Result<()> {
-        if let Some(tx) = &self.cmd_tx {
-            let msg = serde_json::to_string(&order).map_err(|e| Error::Json(Box::new(e)))?;
-            tx.send(msg).await.map_err(|_| Error::ConnectionClosed)?;
+    pub(crate) async fn send_unsubscription(
+        &self,
+        input: SubscribeInput,
+    ) -> Result<Vec<SubscribeResult>> {
+        self.check_token(&input)?;
+        match input.params.channel {
+            Channel::Executions => self.shared.open_orders.clear(),
+            Channel::Balances => self.shared.balances.clear(),
+            _ => {}
         }
-        Ok(())
+        for symbol in &input.params.symbol {
+            match input.params.channel {
+                Channel::Book => self.shared.books.remove(symbol),
+                Channel::Level3 => self.shared.level3.remove(symbol),
+                _ => {}
+            }
+        }
+        let token = input.params.token.clone();
+        let Some(mut input) = self.shared.subscriptions.unsubscribe(input) else {
+            return Ok(Vec::new()); // Nothing active, or only dropped from the queue
+        };
+        // The token the channel was subscribed with may have expired since;
+        // without a new one, the provider supplies the current token.
+        if token.is_some() || self.has_token_provider() {
+            input.params.token = token;
+        }
+        MethodResponse::into_results(self.send_channel_request(input).await?)
     }
 
-    pub async fn cancel_order(&self, cancel: CancelOrderRequest) -> Result<()> {
-        if let Some(tx) = &self.cmd_tx {
-            let msg = serde_json::to_string(&cancel).map_err(|e| Error::Json(Box::new(e)))?;
-            tx.send(msg).await.map_err(|_| Error::ConnectionClosed)?;
+    /// Private channels need a token, given or from the provider.
+    fn check_token(&self, input: &SubscribeInput) -> Result<()> {
+        if input.params.channel.is_private()
+            && input.params.token.is_none()
+            && !self.has_token_provider()
+        {
+            return Err(Error::MissingToken);
         }
         Ok(())
     }
 
-    pub async fn batch_orders(&self, batch: BatchOrderRequest) -> Result<()> {
-        if let Some(tx) = &self.cmd_tx {
-            let msg = serde_json::to_string(&batch).map_err(|e| Error::Json(Box::new(e)))?;
-            tx.send(msg).await.map_err(|_| Error::ConnectionClosed)?;
+    async fn send_channel_request(&self, mut input: SubscribeInput) -> Result<Vec<MethodResponse>> {
+        let req_id = *input
+            .req_id
+            .get_or_insert_with(|| self.shared.requests.next_id());
+        // Kraken acknowledges each symbol separately under the same req_id.
+        let expected = input.params.symbol.len();
+        if input.params.channel.is_private() {
+            return self.send_private(&mut input, expected).await;
+        }
+        self.send(req_id, expected, &input).await
+    }
+
+    pub async fn add_order(&self, mut order: AddOrderRequest) -> Result<AddOrderResult> {
+        let mut results = self.call_private(&mut order, 1).await?;
+        Ok(results.remove(0))
+    }
+
+    /// Changes quantity, price or trigger of a resting order in place, so it
+    /// keeps its order id (and queue priority where Kraken allows).
+    pub async fn amend_order(&self, mut amend: AmendOrderRequest) -> Result<AmendOrderResult> {
+        let mut results = self.call_private(&mut amend, 1).await?;
+        Ok(results.remove(0))
+    }
+
+    /// Replaces a resting order; the result carries the new order id.
+    pub async fn edit_order(&self, mut edit: EditOrderRequest) -> Result<EditOrderResult> {
+        let mut results = self.call_private(&mut edit, 1).await?;
+        Ok(results.remove(0))
+    }
+
+    pub async fn cancel_order(
+        &self,
+        mut cancel: CancelOrderRequest,
+    ) -> Result<Vec<CancelOrderResult>> {
+        let expected = cancel.params.order_id.len();
+        self.call_private(&mut cancel, expected).await
+    }
+
+    /// Cancels every open order on the account.
+    pub async fn cancel_all(&self) -> Result<CancelAllResult> {
+        let mut results = self.call_private(&mut CancelAllRequest::new(), 1).await?;
+        Ok(results.remove(0))
+    }
+
+    /// Arms (or re-arms) Kraken's dead man's switch: every open order is
+    /// canceled once `timeout`, rounded up to whole seconds, passes without
+    /// another call. A zero timeout disarms it; a day or more fails with
+    /// `Error::InvalidTimeout`. See `start_dead_man_switch` to keep it armed.
+    pub async fn cancel_all_orders_after(
+        &self,
+        timeout: std::time::Duration,
+    ) -> Result<CancelAllOrdersAfterResult> {
+        let mut request = CancelAllOrdersAfterRequest::new(timeout);
+        request.check()?;
+        let mut results = self.call_private(&mut request, 1).await?;
+        Ok(results.remove(0))
+    }
+
+    /// Re-arms the dead man's switch with `timeout` every `refresh_every`
+    /// while connected, from a background task. Choose `refresh_every` well
+    /// below `timeout`, e.g. 15s against 60s; a zero or longer one fails
+    /// with `Error::InvalidTimeout`, as does a timeout outside Kraken's limit.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_dead_man_switch(
+        &self,
+        timeout: std::time::Duration,
+        refresh_every: std::time::Duration,
+    ) -> Result<DeadManSwitch> {
+        let request = CancelAllOrdersAfterRequest::new(timeout);
+        request.check()?;
+        if request.params.timeout == 0 {
+            return Err(Error::InvalidTimeout(
+                "zero, which would keep the switch disarmed".to_string(),
+            ));
+        }
+        if refresh_every.is_zero() || refresh_every >= timeout {
+            return Err(Error::InvalidTimeout(format!(
+                "refreshed every {refresh_every:?}, expected a non-zero interval below {timeout:?}"
+            )));
+        }
+        Ok(DeadManSwitch::start(self.clone(), timeout, refresh_every))
+    }
+
+    /// Places 2 to 15 orders on one symbol. Each leg resolves on its own,
+    /// in the order given, since Kraken may reject some and place others.
+    /// A batch breaking Kraken's limits fails with `Error::InvalidBatch`
+    /// before anything is sent.
+    pub async fn batch_orders(
+        &self,
+        mut batch: BatchOrderRequest,
+    ) -> Result<Vec<Result<AddOrderResult>>> {
+        batch.check()?;
+        let mut results = self
+            .call_private::<Vec<serde_json::Value>>(&mut batch, 1)
+            .await?;
+        Ok(results
+            .remove(0)
+            .into_iter()
+            .map(AddOrderResult::from_batch_leg)
+            .collect())
+    }
+
+    /// Cancels up to 50 orders by order id, `order_userref` or `cl_ord_id`.
+    pub async fn batch_cancel(&self, mut batch: BatchCancelRequest) -> Result<BatchCancelResult> {
+        batch.check()?;
+        let mut results = self.call_private(&mut batch, 1).await?;
+        Ok(results.remove(0))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn has_token_provider(&self) -> bool {
+        self.config.token_provider().is_some()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn has_token_provider(&self) -> bool {
+        false
+    }
+
+    async fn call_private<T: DeserializeOwned>(
+        &self,
+        request: &mut impl PrivateRequest,
+        expected: usize,
+    ) -> Result<Vec<T>> {
+        MethodResponse::into_results(self.send_private(request, expected).await?)
+    }
+
+    /// Sends a private request, stamped with the configured provider's token
+    /// unless it already carries one. When Kraken rejects that token, a new
+    /// one is fetched and the request sent once more.
+    async fn send_private(
+        &self,
+        request: &mut impl PrivateRequest,
+        expected: usize,
+    ) -> Result<Vec<MethodResponse>> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(tokens) = self.config.token_provider() {
+            if request.token_mut().is_none() {
+                if !self.shared.state.current().is_connected() {
+                    return Err(Error::NotConnected);
+                }
+                *request.token_mut() = Some(tokens.token().await?);
+                let req_id = *request
+                    .req_id_mut()
+                    .get_or_insert_with(|| self.shared.requests.next_id());
+                let responses = self.send(req_id, expected, &*request).await?;
+                let token_rejected = responses.iter().any(|response| {
+                    !response.success && is_token_error(response.error.as_deref().unwrap_or(""))
+                });
+                if !token_rejected {
+                    return Ok(responses);
+                }
+                *request.token_mut() = Some(tokens.refresh().await?);
+                let req_id = self.shared.requests.next_id();
+                *request.req_id_mut() = Some(req_id);
+                return self.send(req_id, expected, &*request).await;
+            }
+        }
+
+        let req_id = *request
+            .req_id_mut()
+            .get_or_insert_with(|| self.shared.requests.next_id());
+        self.send(req_id, expected, &*request).await
+    }
+
+    /// Sends `request` and resolves once `expected` responses tagged with
+    /// `req_id` have arrived, or an error for the whole request.
+    async fn send(
+        &self,
+        req_id: u64,
+        expected: usize,
+        request: &impl Serialize,
+    ) -> Result<Vec<MethodResponse>> {
+        let tx = match self.cmd_tx.lock().unwrap().clone() {
+            Some(tx) if self.shared.state.current().is_connected() => tx,
+            _ => return Err(Error::NotConnected),
+        };
+        let msg = serde_json::to_string(request).map_err(|e| Error::Json(Box::new(e)))?;
+
+        let rx = self.shared.requests.register(req_id, expected);
+        if tx.send(msg).await.is_err() {
+            self.shared.requests.cancel(req_id);
+            return Err(Error::ConnectionClosed);
+        }
+
+        match tokio::time::timeout(self.config.request_timeout(), rx).await {
+            Ok(Ok(responses)) => Ok(responses),
+            Ok(Err(_)) => Err(Error::ConnectionClosed),
+            Err(_) => {
+                self.shared.requests.cancel(req_id);
+                Err(Error::Timeout)
+            }
         }
-        Ok(())
     }
 }
@@ -1,10 +1,25 @@
 use std::time::Duration;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{Auth, TokenProvider};
+
+pub const PUBLIC_WS_URL: &str = "wss://ws.kraken.com/v2";
+pub const AUTH_WS_URL: &str = "wss://ws-auth.kraken.com/v2";
+/// The only endpoint that serves the `level3` channel.
+pub const LEVEL3_WS_URL: &str = "wss://ws-l3.kraken.com/v2";
+
 #[derive(Debug, Clone)]
 pub struct Config {
     ws_url: String,
     max_retries: u32,
     initial_backoff: Duration,
+    max_backoff: Duration,
+    auto_reconnect: bool,
+    ping_interval: Option<Duration>,
+    heartbeat_timeout: Option<Duration>,
+    request_timeout: Duration,
+    #[cfg(not(target_arch = "wasm32"))]
+    token_provider: Option<TokenProvider>,
 }
 
 impl Config {
@@ -23,6 +38,31 @@ impl Config {
     pub fn initial_backoff(&self) -> Duration {
         self.initial_backoff
     }
+
+    pub fn max_backoff(&self) -> Duration {
+        self.max_backoff
+    }
+
+    pub fn auto_reconnect(&self) -> bool {
+        self.auto_reconnect
+    }
+
+    pub fn ping_interval(&self) -> Option<Duration> {
+        self.ping_interval
+    }
+
+    pub fn heartbeat_timeout(&self) -> Option<Duration> {
+        self.heartbeat_timeout
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        self.request_timeout
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn token_provider(&self) -> Option<&TokenProvider> {
+        self.token_provider.as_ref()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -30,14 +70,29 @@ pub struct Builder {
     ws_url: Option<String>,
     max_retries: Option<u32>,
     initial_backoff: Option<Duration>,
+    max_backoff: Option<Duration>,
+    auto_reconnect: Option<bool>,
+    ping_interval: Option<Option<Duration>>,
+    heartbeat_timeout: Option<Option<Duration>>,
+    request_timeout: Option<Duration>,
+    #[cfg(not(target_arch = "wasm32"))]
+    token_provider: Option<TokenProvider>,
 }
 
 impl Default for Builder {
     fn default() -> Self {
         Self {
-            ws_url: Some("wss://ws.kraken.com/v2".to_string()),
+            // Left unset so `build` can pick the endpoint matching the credentials.
+            ws_url: None,
             max_retries: Some(3),
             initial_backoff: Some(Duration::from_millis(1000)),
+            max_backoff: Some(Duration::from_secs(30)),
+            auto_reconnect: Some(true),
+            ping_interval: Some(Some(Duration::from_secs(30))),
+            heartbeat_timeout: Some(Some(Duration::from_secs(60))),
+            request_timeout: Some(Duration::from_secs(10)),
+            #[cfg(not(target_arch = "wasm32"))]
+            token_provider: None,
         }
     }
 }
@@ -58,13 +113,78 @@ impl Builder {
         self
     }
 
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = Some(backoff);
+        self
+    }
+
+    /// Reconnect and replay subscriptions when a live connection drops.
+    pub fn auto_reconnect(mut self, enabled: bool) -> Self {
+        self.auto_reconnect = Some(enabled);
+        self
+    }
+
+    /// How often to send a `ping`; `None` disables keepalive pings.
+    pub fn ping_interval(mut self, interval: impl Into<Option<Duration>>) -> Self {
+        self.ping_interval = Some(interval.into());
+        self
+    }
+
+    /// Declare the connection dead when nothing (heartbeat, pong or data)
+    /// arrives for this long; `None` disables the watchdog.
+    pub fn heartbeat_timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.heartbeat_timeout = Some(timeout.into());
+        self
+    }
+
+    /// How long request methods wait for the exchange's response.
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// API credentials for private channels and trading. The client fetches
+    /// WebSocket tokens with them from Kraken's REST API and, unless `ws_url`
+    /// is set, connects to the authenticated endpoint.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn auth(self, auth: Auth) -> Self {
+        self.token_provider(TokenProvider::new(auth))
+    }
+
+    /// Like `auth`, with a provider configured separately (REST base URL,
+    /// refresh margin) or shared with other clients.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn token_provider(mut self, provider: TokenProvider) -> Self {
+        self.token_provider = Some(provider);
+        self
+    }
+
     pub fn build(self) -> Config {
+        #[cfg(not(target_arch = "wasm32"))]
+        let authenticated = self.token_provider.is_some();
+        #[cfg(target_arch = "wasm32")]
+        let authenticated = false;
+
         Config {
-            ws_url: self
-                .ws_url
-                .unwrap_or_else(|| "wss://ws.kraken.com/v2".to_string()),
+            ws_url: self.ws_url.unwrap_or_else(|| {
+                if authenticated {
+                    AUTH_WS_URL
+                } else {
+                    PUBLIC_WS_URL
+                }
+                .to_string()
+            }),
             max_retries: self.max_retries.unwrap_or(3),
             initial_backoff: self.initial_backoff.unwrap_or(Duration::from_millis(1000)),
+            max_backoff: self.max_backoff.unwrap_or(Duration::from_secs(30)),
+            auto_reconnect: self.auto_reconnect.unwrap_or(true),
+            ping_interval: self.ping_interval.unwrap_or(Some(Duration::from_secs(30))),
+            heartbeat_timeout: self
+                .heartbeat_timeout
+                .unwrap_or(Some(Duration::from_secs(60))),
+            request_timeout: self.request_timeout.unwrap_or(Duration::from_secs(10)),
+            #[cfg(not(target_arch = "wasm32"))]
+            token_provider: self.token_provider,
         }
     }
 }
@@ -79,11 +199,21 @@ mod tests {
             .ws_url("wss://test.com")
             .max_retries(5)
             .initial_backoff(Duration::from_millis(500))
+            .max_backoff(Duration::from_secs(5))
+            .auto_reconnect(false)
+            .ping_interval(Duration::from_secs(10))
+            .heartbeat_timeout(None)
+            .request_timeout(Duration::from_secs(2))
             .build();
 
         assert_eq!(config.ws_url(), "wss://test.com");
         assert_eq!(config.max_retries(), 5);
         assert_eq!(config.initial_backoff(), Duration::from_millis(500));
+        assert_eq!(config.max_backoff(), Duration::from_secs(5));
+        assert!(!config.auto_reconnect());
+        assert_eq!(config.ping_interval(), Some(Duration::from_secs(10)));
+        assert_eq!(config.heartbeat_timeout(), None);
+        assert_eq!(config.request_timeout(), Duration::from_secs(2));
     }
 
     #[test]
@@ -93,5 +223,25 @@ mod tests {
         assert_eq!(config.ws_url(), "wss://ws.kraken.com/v2");
         assert_eq!(config.max_retries(), 3);
         assert_eq!(config.initial_backoff(), Duration::from_millis(1000));
+        assert_eq!(config.max_backoff(), Duration::from_secs(30));
+        assert!(config.auto_reconnect());
+        assert_eq!(config.ping_interval(), Some(Duration::from_secs(30)));
+        assert_eq!(config.heartbeat_timeout(), Some(Duration::from_secs(60)));
+        assert_eq!(config.request_timeout(), Duration::from_secs(10));
+        assert!(config.token_provider().is_none());
+    }
+
+    #[test]
+    fn test_auth_selects_private_endpoint() {
+        let auth = Auth::new("key".to_string(), "c2VjcmV0".to_string());
+        let config = Config::builder().auth(auth.clone()).build();
+        assert_eq!(config.ws_url(), AUTH_WS_URL);
+        assert!(config.token_provider().is_some());
+
+        let config = Config::builder()
+            .ws_url("wss://ws-l3.kraken.com/v2")
+            .auth(auth)
+            .build();
+        assert_eq!(config.ws_url(), "wss://ws-l3.kraken.com/v2");
     }
 }
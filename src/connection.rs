@@ -0,0 +1,492 @@
+use futures_util::{SinkExt, StreamExt};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::time::{interval_at, sleep, Instant};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use url::Url;
+
+use crate::{
+    book::{BookFault, ResyncLimiter, DEFAULT_DEPTH},
+    candle::CandleTracker,
+    client::Shared,
+    parser::{DecodeError, Parser},
+    protocol::{event::KrakenEvent, ping::PingMessage},
+    state::{ConnectionState, StateTracker},
+    types::{Channel, SubscribeInput, Subscription},
+    Config, Error, Result,
+};
+
+pub(crate) type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+pub(crate) async fn open(config: &Config) -> Result<WsStream> {
+    let url = Url::parse(config.ws_url())?;
+    let (ws_stream, _) = connect_async(url)
+        .await
+        .map_err(|e| Error::WebSocket(Box::new(e)))?;
+    Ok(ws_stream)
+}
+
+pub(crate) async fn open_with_retry(
+    config: &Config,
+    state: &StateTracker,
+    reconnecting: bool,
+) -> Result<WsStream> {
+    let mut attempt = 0;
+    let mut backoff = config.initial_backoff();
+
+    loop {
+        state.set(if attempt == 0 && !reconnecting {
+            ConnectionState::Connecting
+        } else {
+            ConnectionState::Reconnecting {
+                attempt: attempt + u32::from(reconnecting),
+            }
+        });
+
+        match open(config).await {
+            Ok(ws) => {
+                state.set(ConnectionState::Connected);
+                return Ok(ws);
+            }
+            Err(e) => {
+                attempt += 1;
+                if attempt > config.max_retries() {
+                    state.set(ConnectionState::GaveUp);
+                    return Err(e);
+                }
+                sleep(backoff).await;
+                backoff = (backoff * 2).min(config.max_backoff()); // Exponential backoff
+            }
+        }
+    }
+}
+
+async fn send_json(ws: &mut WsStream, message: &impl serde::Serialize) -> Result<()> {
+    let text = serde_json::to_string(message).map_err(|e| Error::Json(Box::new(e)))?;
+    ws.send(Message::Text(text))
+        .await
+        .map_err(|e| Error::WebSocket(Box::new(e)))
+}
+
+/// Period used for disabled timers; they are never polled in that case.
+const IDLE_PERIOD: Duration = Duration::from_secs(3600);
+
+enum Exit {
+    Disconnected(String),
+    Shutdown,
+}
+
+/// Where the session delivers what it reads.
+#[derive(Clone)]
+pub(crate) enum Sink {
+    Events(mpsc::Sender<KrakenEvent>),
+    /// Undecoded frames for `RawStream`, which parses them in place.
+    #[cfg(feature = "zero-copy")]
+    Frames(mpsc::Sender<Vec<u8>>),
+}
+
+impl Sink {
+    fn is_closed(&self) -> bool {
+        match self {
+            Sink::Events(tx) => tx.is_closed(),
+            #[cfg(feature = "zero-copy")]
+            Sink::Frames(tx) => tx.is_closed(),
+        }
+    }
+}
+
+/// Owns the socket for the lifetime of a `Client` connection, reconnecting
+/// and replaying subscriptions whenever the live socket drops.
+pub(crate) struct Session {
+    config: Config,
+    cmd_rx: mpsc::Receiver<String>,
+    commands_open: bool,
+    sink: Sink,
+    shared: Shared,
+    pending_ping: Option<(u64, Instant)>,
+    /// Books to resubscribe, and when.
+    stale_books: HashMap<String, Instant>,
+    resyncs: ResyncLimiter,
+    /// Books dropped for an unknown precision, resubscribed once the
+    /// instrument registry knows it.
+    awaiting_precision: HashSet<String>,
+    candles: CandleTracker,
+}
+
+impl Session {
+    pub(crate) fn new(
+        config: Config,
+        cmd_rx: mpsc::Receiver<String>,
+        sink: Sink,
+        shared: Shared,
+    ) -> Self {
+        Self {
+            config,
+            cmd_rx,
+            commands_open: true,
+            sink,
+            shared,
+            pending_ping: None,
+            stale_books: HashMap::new(),
+            resyncs: ResyncLimiter::default(),
+            awaiting_precision: HashSet::new(),
+            candles: CandleTracker::default(),
+        }
+    }
+
+    pub(crate) async fn run(mut self, mut ws: WsStream) {
+        loop {
+            let reason = match self.drive(&mut ws).await {
+                Exit::Disconnected(reason) => reason,
+                Exit::Shutdown => {
+                    let _ = ws.close(None).await;
+                    self.shared.subscriptions.go_offline();
+                    self.shared.state.set(ConnectionState::Disconnected {
+                        reason: "stream dropped".to_string(),
+                    });
+                    return;
+                }
+            };
+            self.shared.subscriptions.go_offline();
+            self.shared.books.clear(); // Stale until the replayed snapshot arrives
+            self.shared.level3.clear();
+            self.shared.requests.fail_all();
+            self.shared
+                .state
+                .set(ConnectionState::Disconnected { reason });
+
+            if !self.config.auto_reconnect() || self.sink.is_closed() {
+                return;
+            }
+
+            ws = match open_with_retry(&self.config, &self.shared.state, true).await {
+                Ok(ws) => ws,
+                Err(_) => return,
+            };
+            // A failed replay surfaces as a read error on the next drive.
+            let _ = self.resume(&mut ws).await;
+        }
+    }
+
+    /// Sends every registered subscription on a fresh socket: the ones queued
+    /// before the first connect, or the ones lost with the previous socket.
+    /// With a token provider, private channels get its current token, even
+    /// if they were first sent with one of their own: that may have expired
+    /// since.
+    pub(crate) async fn resume(&self, ws: &mut WsStream) -> Result<()> {
+        let mut inputs = self.shared.subscriptions.go_live();
+        if let Some(tokens) = self.config.token_provider() {
+            for input in &mut inputs {
+                if input.params.channel.is_private() {
+                    input.params.token = Some(tokens.token().await?);
+                }
+            }
+        }
+
+        let messages = inputs
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| Error::Json(Box::new(e)))?;
+
+        for msg in messages {
+            ws.send(Message::Text(msg))
+                .await
+                .map_err(|e| Error::WebSocket(Box::new(e)))?;
+        }
+        Ok(())
+    }
+
+    async fn drive(&mut self, ws: &mut WsStream) -> Exit {
+        // Timers are always armed but only polled when the feature is enabled.
+        let ping_every = self.config.ping_interval();
+        let mut ping_timer = {
+            let period = ping_every.unwrap_or(IDLE_PERIOD);
+            interval_at(Instant::now() + period, period)
+        };
+
+        let heartbeat_timeout = self.config.heartbeat_timeout();
+        let mut last_alive = Instant::now();
+        let watchdog = sleep(heartbeat_timeout.unwrap_or(IDLE_PERIOD));
+        tokio::pin!(watchdog);
+
+        let resync_timer = sleep(IDLE_PERIOD);
+        tokio::pin!(resync_timer);
+
+        self.pending_ping = None;
+        self.stale_books.clear(); // The replay brings fresh snapshots
+        self.awaiting_precision.clear();
+        self.candles.clear(); // Updates missed while offline may have changed them
+
+        loop {
+            if let Some(due) = self.stale_books.values().min() {
+                resync_timer.as_mut().reset(*due);
+            }
+            tokio::select! {
+                cmd = self.cmd_rx.recv(), if self.commands_open => match cmd {
+                    Some(msg) => {
+                        if let Err(e) = ws.send(Message::Text(msg)).await {
+                            return Exit::Disconnected(e.to_string());
+                        }
+                    }
+                    None => self.commands_open = false,
+                },
+                _ = ping_timer.tick(), if ping_every.is_some() => {
+                    if let Err(e) = self.send_ping(ws).await {
+                        return Exit::Disconnected(e.to_string());
+                    }
+                }
+                () = &mut watchdog, if heartbeat_timeout.is_some() => {
+                    let deadline = last_alive + heartbeat_timeout.unwrap_or(IDLE_PERIOD);
+                    if Instant::now() >= deadline {
+                        return Exit::Disconnected("heartbeat timeout".to_string());
+                    }
+                    watchdog.as_mut().reset(deadline);
+                }
+                () = &mut resync_timer, if !self.stale_books.is_empty() => {
+                    if let Err(e) = self.resync_books(ws).await {
+                        return Exit::Disconnected(e.to_string());
+                    }
+                }
+                msg = ws.next() => match msg {
+                    Some(Ok(Message::Close(frame))) => {
+                        let reason = match frame {
+                            Some(frame) => format!("closed by server: {} {}", frame.code, frame.reason),
+                            None => "closed by server".to_string(),
+                        };
+                        return Exit::Disconnected(reason);
+                    }
+                    Some(Ok(msg)) => {
+                        last_alive = Instant::now();
+                        if !self.deliver(msg).await {
+                            return Exit::Shutdown; // Backpressure: receiver dropped
+                        }
+                    }
+                    Some(Err(e)) => return Exit::Disconnected(e.to_string()),
+                    None => return Exit::Disconnected("connection closed".to_string()),
+                },
+            }
+        }
+    }
+
+    async fn send_ping(&mut self, ws: &mut WsStream) -> Result<()> {
+        let req_id = self.shared.requests.next_id();
+        send_json(ws, &PingMessage::new(req_id)).await?;
+        self.pending_ping = Some((req_id, Instant::now()));
+        Ok(())
+    }
+
+    /// Re-subscribes the books that are due. Kraken only sends a new
+    /// snapshot to a fresh subscription, so each is dropped and requested
+    /// again with its original options.
+    async fn resync_books(&mut self, ws: &mut WsStream) -> Result<()> {
+        let now = Instant::now();
+        let due: Vec<String> = self
+            .stale_books
+            .iter()
+            .filter(|(_, at)| **at <= now)
+            .map(|(symbol, _)| symbol.clone())
+            .collect();
+        for symbol in due {
+            self.stale_books.remove(&symbol);
+            let key = Subscription {
+                channel: Channel::Book,
+                symbol: Some(symbol),
+            };
+            let Some(params) = self.shared.subscriptions.params(&key) else {
+                continue; // Unsubscribed in the meantime
+            };
+            for method in ["unsubscribe", "subscribe"] {
+                let input = SubscribeInput {
+                    method: method.to_string(),
+                    params: params.clone(),
+                    req_id: Some(self.shared.requests.next_id()),
+                };
+                send_json(ws, &input).await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn record_pong(&mut self, req_id: Option<u64>) {
+        if let Some((id, sent_at)) = self.pending_ping {
+            if req_id == Some(id) {
+                self.shared.metrics.record_rtt(sent_at.elapsed());
+                self.pending_ping = None;
+            }
+        }
+    }
+
+    fn book_depth(&self, symbol: &str) -> u32 {
+        let key = Subscription {
+            channel: Channel::Book,
+            symbol: Some(symbol.to_string()),
+        };
+        self.shared
+            .subscriptions
+            .params(&key)
+            .and_then(|params| params.depth)
+            .unwrap_or(DEFAULT_DEPTH)
+    }
+
+    /// Returns false once the receiving side has gone away.
+    async fn deliver(&mut self, msg: Message) -> bool {
+        match self.sink.clone() {
+            Sink::Events(tx) => {
+                let Some(event) = self.decode(&msg) else {
+                    return true;
+                };
+                // Events the session derives from this one, sent right after it.
+                let mut derived = Vec::new();
+                match &event {
+                    KrakenEvent::Pong(pong) => self.record_pong(pong.req_id),
+                    KrakenEvent::Orderbook(book) => {
+                        let faults = self
+                            .shared
+                            .books
+                            .apply(book, |symbol| self.book_depth(symbol));
+                        for fault in faults {
+                            match fault {
+                                BookFault::Mismatch(resync) => {
+                                    let now = Instant::now();
+                                    match self.resyncs.next_delay(&resync.symbol, now.into_std()) {
+                                        Some(delay) => {
+                                            self.stale_books
+                                                .insert(resync.symbol.clone(), now + delay);
+                                        }
+                                        None => self.report_decode_error(
+                                            Error::ResyncLimit(resync.symbol.clone()),
+                                            msg.to_string(),
+                                        ),
+                                    }
+                                    derived.push(KrakenEvent::BookResync(resync));
+                                }
+                                // Re-subscribing would not help until the
+                                // instrument registry knows the precision.
+                                BookFault::UnknownPrecision(symbol) => {
+                                    self.awaiting_precision.insert(symbol.clone());
+                                    self.report_decode_error(
+                                        Error::UnknownPrecision(symbol),
+                                        msg.to_string(),
+                                    );
+                                }
+                            }
+                        }
+                    }
+                    KrakenEvent::Level3(level3) => self.shared.level3.apply(level3),
+                    KrakenEvent::Executions(executions) => {
+                        self.shared.open_orders.apply(executions)
+                    }
+                    KrakenEvent::Balances(balances) => self.shared.balances.apply(balances),
+                    KrakenEvent::Instrument(instruments) => {
+                        self.shared.instruments.apply(instruments);
+                        let instruments = &self.shared.instruments;
+                        let now = Instant::now();
+                        self.awaiting_precision.retain(|symbol| {
+                            if instruments.precision(symbol).is_none() {
+                                return true;
+                            }
+                            self.stale_books.insert(symbol.clone(), now);
+                            false
+                        });
+                    }
+                    KrakenEvent::Ohlc(ohlc) => derived.extend(
+                        self.candles
+                            .update(ohlc)
+                            .into_iter()
+                            .map(KrakenEvent::CandleClosed),
+                    ),
+                    KrakenEvent::Unknown(_) => self.shared.metrics.record_unknown(),
+                    _ => {}
+                }
+                for event in std::iter::once(event).chain(derived) {
+                    if tx.send(event).await.is_err() {
+                        return false;
+                    }
+                }
+                true
+            }
+            #[cfg(feature = "zero-copy")]
+            Sink::Frames(tx) => {
+                let Some(frame) = self.inspect(msg) else {
+                    return true;
+                };
+                tx.send(frame).await.is_ok()
+            }
+        }
+    }
+
+    /// Handles the session's own traffic in a raw frame and hands the
+    /// bytes back untouched; decoding is left to the consumer.
+    #[cfg(feature = "zero-copy")]
+    fn inspect(&mut self, msg: Message) -> Option<Vec<u8>> {
+        use crate::parser::Route;
+
+        let frame = match msg {
+            Message::Text(text) => text.into_bytes(),
+            Message::Binary(data) => data,
+            _ => return None,
+        };
+        match Parser::peek(&frame) {
+            Some((Route::Pong, req_id)) => self.record_pong(req_id),
+            // Reference data and account updates are rare and small enough
+            // to decode twice.
+            Some((Route::Instrument, _)) => {
+                if let Ok(instruments) = serde_json::from_slice(&frame) {
+                    self.shared.instruments.apply(&instruments);
+                }
+            }
+            Some((Route::Executions, _)) => {
+                if let Ok(executions) = serde_json::from_slice(&frame) {
+                    self.shared.open_orders.apply(&executions);
+                }
+            }
+            Some((Route::Balances, _)) => {
+                if let Ok(balances) = serde_json::from_slice(&frame) {
+                    self.shared.balances.apply(&balances);
+                }
+            }
+            Some((Route::Order | Route::Response, _)) => {
+                if let Ok(response) = serde_json::from_slice(&frame) {
+                    self.shared.requests.resolve(response);
+                }
+            }
+            _ => {}
+        }
+        Some(frame)
+    }
+
+    fn decode(&self, msg: &Message) -> Option<KrakenEvent> {
+        let text = match msg {
+            Message::Text(text) => text.as_str(),
+            // Kraken only sends text frames; a binary one is decoded the same way.
+            Message::Binary(data) => match std::str::from_utf8(data) {
+                Ok(text) => text,
+                Err(e) => {
+                    let raw = String::from_utf8_lossy(data).into_owned();
+                    self.report_decode_error(e.into(), raw);
+                    return None;
+                }
+            },
+            _ => return None,
+        };
+        let event = Parser::parse_standard(text)
+            .map_err(|e| self.report_decode_error(e, text.to_string()))
+            .ok()?;
+        if let KrakenEvent::Order(response) | KrakenEvent::Response(response) = &event {
+            self.shared.requests.resolve(response.clone());
+        }
+        Some(event)
+    }
+
+    fn report_decode_error(&self, error: Error, raw: String) {
+        self.shared.metrics.record_decode_error();
+        // Nobody listening is fine; the counter still records it.
+        let _ = self.shared.decode_errors.send(DecodeError {
+            error: error.to_string(),
+            raw,
+        });
+    }
+}
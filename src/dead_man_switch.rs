@@ -0,0 +1,76 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use crate::{types::CancelAllOrdersAfterResult, Client, Result};
+
+/// What `last_error` reports for a refresh skipped while disconnected.
+const NOT_CONNECTED: &str = "not connected";
+
+/// Keeps Kraken's dead man's switch armed from a background task, so open
+/// orders are canceled `timeout` after this process stops refreshing it.
+///
+/// Refreshes are only sent while the connection is up: when the socket
+/// drops, or the process stalls, Kraken's timer runs out on its own.
+/// Dropping the handle stops the refreshes but leaves the switch armed;
+/// `disarm` turns it off.
+#[derive(Debug)]
+pub struct DeadManSwitch {
+    client: Client,
+    task: JoinHandle<()>,
+    last_error: Arc<Mutex<Option<String>>>,
+}
+
+impl DeadManSwitch {
+    /// `refresh_every` must be non-zero and shorter than `timeout`, which
+    /// `Client::start_dead_man_switch` checks.
+    pub(crate) fn start(client: Client, timeout: Duration, refresh_every: Duration) -> Self {
+        let last_error = Arc::new(Mutex::new(None));
+        let task = tokio::spawn({
+            let client = client.clone();
+            let last_error = last_error.clone();
+            async move {
+                let mut ticks = tokio::time::interval(refresh_every);
+                ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                loop {
+                    ticks.tick().await;
+                    // A failed or skipped refresh is retried on the next tick,
+                    // still before the timeout runs out.
+                    let error = if client.connection_state().is_connected() {
+                        let result = client.cancel_all_orders_after(timeout).await;
+                        result.err().map(|e| e.to_string())
+                    } else {
+                        Some(NOT_CONNECTED.to_string())
+                    };
+                    *last_error.lock().unwrap() = error;
+                }
+            }
+        });
+        Self {
+            client,
+            task,
+            last_error,
+        }
+    }
+
+    /// Why the latest refresh failed, or `None` if it succeeded (or none
+    /// was due yet). While the connection is down every refresh is skipped
+    /// and this reads "not connected". Watch it to learn that orders may
+    /// soon be canceled.
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().unwrap().clone()
+    }
+
+    /// Stops the refreshes and disarms the switch, leaving open orders alone.
+    pub async fn disarm(self) -> Result<CancelAllOrdersAfterResult> {
+        self.task.abort();
+        self.client.cancel_all_orders_after(Duration::ZERO).await
+    }
+}
+
+impl Drop for DeadManSwitch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -23,4 +23,42 @@ pub enum Error {
 
     #[error("Connection closed")]
     ConnectionClosed,
+
+    #[error("Not connected: call connect() before sending requests")]
+    NotConnected,
+
+    #[error("{method} failed: {message}")]
+    Api { method: String, message: String },
+
+    #[error("Timed out waiting for a response")]
+    Timeout,
+
+    #[error("Authenticated request without a WebSocket token")]
+    MissingToken,
+
+    #[error("{channel} is not served on {url}; set ws_url to {expected}")]
+    WrongEndpoint {
+        channel: String,
+        url: String,
+        expected: String,
+    },
+
+    #[error("{0} book dropped: its price and quantity precision are unknown, so its checksum cannot be verified")]
+    UnknownPrecision(String),
+
+    #[error("{0} book failed its checksum after every resync; it is no longer resubscribed")]
+    ResyncLimit(String),
+
+    #[error("Invalid batch: {0}")]
+    InvalidBatch(String),
+
+    #[error("Invalid dead man's switch timeout: {0}")]
+    InvalidTimeout(String),
+
+    #[error("API secret is not valid base64: {0}")]
+    InvalidSecret(String),
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("HTTP error: {0}")]
+    Http(#[from] Box<reqwest::Error>),
 }
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::protocol::event::MessageType;
+pub use crate::protocol::executions::{
+    ExecType, ExecutionReport, ExecutionsWrapper, Fee, Liquidity, OrderStatus,
+};
+
+impl ExecutionReport {
+    /// Copies every field `update` carries over this report.
+    pub fn merge(&mut self, update: &ExecutionReport) {
+        fn overlay<T: Clone>(field: &mut Option<T>, update: &Option<T>) {
+            if update.is_some() {
+                field.clone_from(update);
+            }
+        }
+
+        self.exec_type = update.exec_type;
+        self.timestamp.clone_from(&update.timestamp);
+        overlay(&mut self.order_status, &update.order_status);
+        overlay(&mut self.exec_id, &update.exec_id);
+        overlay(&mut self.trade_id, &update.trade_id);
+        overlay(&mut self.cl_ord_id, &update.cl_ord_id);
+        overlay(&mut self.order_userref, &update.order_userref);
+        overlay(&mut self.symbol, &update.symbol);
+        overlay(&mut self.side, &update.side);
+        overlay(&mut self.order_type, &update.order_type);
+        overlay(&mut self.time_in_force, &update.time_in_force);
+        overlay(&mut self.order_qty, &update.order_qty);
+        overlay(&mut self.limit_price, &update.limit_price);
+        overlay(&mut self.last_qty, &update.last_qty);
+        overlay(&mut self.last_price, &update.last_price);
+        overlay(&mut self.cost, &update.cost);
+        overlay(&mut self.cum_qty, &update.cum_qty);
+        overlay(&mut self.cum_cost, &update.cum_cost);
+        overlay(&mut self.avg_price, &update.avg_price);
+        overlay(&mut self.liquidity_ind, &update.liquidity_ind);
+        overlay(&mut self.fees, &update.fees);
+        overlay(&mut self.fee_usd_equiv, &update.fee_usd_equiv);
+        overlay(&mut self.amended, &update.amended);
+        overlay(&mut self.reason, &update.reason);
+    }
+
+    /// Whether this report takes the order off the book.
+    pub fn closes_order(&self) -> bool {
+        self.order_status.is_some_and(OrderStatus::is_closed)
+            || matches!(
+                self.exec_type,
+                ExecType::Filled | ExecType::Canceled | ExecType::Expired
+            )
+    }
+}
+
+/// The account's open orders, built from the `executions` snapshot and
+/// kept current from its updates.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OpenOrders {
+    orders: Arc<Mutex<HashMap<String, ExecutionReport>>>,
+}
+
+impl OpenOrders {
+    /// A snapshot replaces the open orders; trades it replays are history
+    /// and are skipped. Each update is merged into its order, which is
+    /// dropped once closed.
+    pub(crate) fn apply(&self, message: &ExecutionsWrapper) {
+        let mut orders = self.orders.lock().unwrap();
+        if message.event_type == MessageType::Snapshot {
+            orders.clear();
+        }
+        for report in &message.data {
+            let snapshot_trade =
+                message.event_type == MessageType::Snapshot && report.exec_type == ExecType::Trade;
+            if snapshot_trade {
+                continue;
+            }
+            if report.closes_order() {
+                orders.remove(&report.order_id);
+                continue;
+            }
+            match orders.get_mut(&report.order_id) {
+                Some(order) => order.merge(report),
+                // Something this SDK does not know about an order it never
+                // saw opened is not a reason to track it.
+                None if report.exec_type == ExecType::Unknown => {}
+                None => {
+                    orders.insert(report.order_id.clone(), report.clone());
+                }
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, order_id: &str) -> Option<ExecutionReport> {
+        self.orders.lock().unwrap().get(order_id).cloned()
+    }
+
+    pub(crate) fn list(&self) -> Vec<ExecutionReport> {
+        self.orders.lock().unwrap().values().cloned().collect()
+    }
+
+    pub(crate) fn clear(&self) {
+        self.orders.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OrderType, Side, TimeInForce};
+    use rust_decimal_macros::dec;
+
+    fn message(kind: &str, data: &str) -> ExecutionsWrapper {
+        let text = format!(
+            r#"{{"channel":"executions","type":"{}","data":[{}],"sequence":1}}"#,
+            kind, data
+        );
+        serde_json::from_str(&text).unwrap()
+    }
+
+    #[test]
+    fn test_open_orders_follow_fills() {
+        let orders = OpenOrders::default();
+        orders.apply(&message(
+            "snapshot",
+            r#"{"order_id":"OA","exec_type":"new","order_status":"new","symbol":"BTC/USD",
+                "side":"buy","order_type":"limit","order_qty":1.0,"limit_price":42000.0,
+                "cl_ord_id":"mine-1","order_userref":7,"timestamp":"2024-01-01T00:00:00Z"}"#,
+        ));
+
+        orders.apply(&message(
+            "update",
+            r#"{"order_id":"OA","exec_type":"trade","order_status":"partially_filled",
+                "exec_id":"TX","last_qty":0.4,"last_price":42000.0,"cum_qty":0.4,
+                "liquidity_ind":"m","fees":[{"asset":"USD","qty":6.72}],
+                "timestamp":"2024-01-01T00:00:01Z"}"#,
+        ));
+        let order = orders.get("OA").unwrap();
+        assert_eq!(order.order_status, Some(OrderStatus::PartiallyFilled));
+        assert_eq!(order.cum_qty, Some(dec!(0.4)));
+        assert_eq!(order.cl_ord_id.as_deref(), Some("mine-1"));
+        assert_eq!(order.liquidity_ind, Some(Liquidity::Maker));
+        assert_eq!(order.fees.unwrap()[0].qty, dec!(6.72));
+
+        orders.apply(&message(
+            "update",
+            r#"{"order_id":"OA","exec_type":"filled","order_status":"filled",
+                "timestamp":"2024-01-01T00:00:02Z"}"#,
+        ));
+        assert!(orders.list().is_empty());
+
+        let future = message(
+            "update",
+            r#"{"order_id":"OB","exec_type":"something_new","timestamp":"2024-01-01T00:00:03Z"}"#,
+        );
+        assert_eq!(future.data[0].exec_type, ExecType::Unknown);
+        orders.apply(&future);
+        assert!(orders.get("OB").is_none());
+    }
+
+    #[test]
+    fn test_reports_use_order_enums() {
+        let orders = OpenOrders::default();
+        orders.apply(&message(
+            "snapshot",
+            r#"{"order_id":"OA","exec_type":"new","order_status":"new","symbol":"BTC/USD",
+                "side":"sell","order_type":"stop-loss-limit","time_in_force":"gtd",
+                "timestamp":"2024-01-01T00:00:00Z"}"#,
+        ));
+        let order = orders.get("OA").unwrap();
+        assert_eq!(order.side, Some(Side::Sell));
+        assert_eq!(order.order_type, Some(OrderType::StopLossLimit));
+        assert_eq!(order.time_in_force, Some(TimeInForce::Gtd));
+
+        let future = message(
+            "update",
+            r#"{"order_id":"OA","exec_type":"amended","order_type":"brand-new",
+                "time_in_force":"fok","timestamp":"2024-01-01T00:00:01Z"}"#,
+        );
+        assert_eq!(future.data[0].order_type, Some(OrderType::Unknown));
+        assert_eq!(future.data[0].time_in_force, Some(TimeInForce::Unknown));
+    }
+}
@@ -0,0 +1,154 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::book::Precision;
+use crate::protocol::event::MessageType;
+pub use crate::protocol::instrument::{AssetInfo, InstrumentData, InstrumentWrapper, PairInfo};
+
+impl PairInfo {
+    pub fn precision(&self) -> Precision {
+        Precision {
+            price: self.price_precision,
+            qty: self.qty_precision,
+        }
+    }
+
+    /// Rounds to the nearest valid price for this pair; a price halfway
+    /// between two ticks goes to the one further from zero.
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        let mut rounded = Self::to_increment(price, self.price_increment, |ticks| {
+            ticks.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+        });
+        rounded.rescale(self.price_precision);
+        rounded
+    }
+
+    /// Rounds down to a valid order quantity, so it never exceeds `qty`.
+    pub fn round_qty(&self, qty: Decimal) -> Decimal {
+        let mut rounded = Self::to_increment(qty, self.qty_increment, Decimal::floor);
+        rounded.rescale(self.qty_precision);
+        rounded
+    }
+
+    /// Whether Kraken accepts new orders of any kind for this pair.
+    pub fn is_online(&self) -> bool {
+        self.status == "online"
+    }
+
+    fn to_increment(value: Decimal, increment: Decimal, round: fn(&Decimal) -> Decimal) -> Decimal {
+        if increment.is_zero() {
+            return value;
+        }
+        round(&(value / increment)) * increment
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    assets: HashMap<String, AssetInfo>,
+    pairs: HashMap<String, PairInfo>,
+}
+
+/// Pair and asset reference data from the `instrument` channel, kept up to
+/// date by the session and shared by every clone of a `Client`.
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentRegistry {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl InstrumentRegistry {
+    /// A snapshot replaces everything known; an update adds or replaces
+    /// the assets and pairs it lists.
+    pub(crate) fn apply(&self, message: &InstrumentWrapper) {
+        let mut inner = self.inner.lock().unwrap();
+        if message.event_type == MessageType::Snapshot {
+            inner.assets.clear();
+            inner.pairs.clear();
+        }
+        for asset in &message.data.assets {
+            inner.assets.insert(asset.id.clone(), asset.clone());
+        }
+        for pair in &message.data.pairs {
+            inner.pairs.insert(pair.symbol.clone(), pair.clone());
+        }
+    }
+
+    pub fn pair(&self, symbol: &str) -> Option<PairInfo> {
+        self.inner.lock().unwrap().pairs.get(symbol).cloned()
+    }
+
+    pub fn asset(&self, id: &str) -> Option<AssetInfo> {
+        self.inner.lock().unwrap().assets.get(id).cloned()
+    }
+
+    pub fn precision(&self, symbol: &str) -> Option<Precision> {
+        self.inner
+            .lock()
+            .unwrap()
+            .pairs
+            .get(symbol)
+            .map(PairInfo::precision)
+    }
+
+    pub fn pairs(&self) -> Vec<PairInfo> {
+        self.inner.lock().unwrap().pairs.values().cloned().collect()
+    }
+
+    pub fn assets(&self) -> Vec<AssetInfo> {
+        self.inner
+            .lock()
+            .unwrap()
+            .assets
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let inner = self.inner.lock().unwrap();
+        inner.pairs.is_empty() && inner.assets.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn message(kind: &str, pairs: &str) -> InstrumentWrapper {
+        let text = format!(
+            r#"{{"channel":"instrument","type":"{}","data":{{"assets":[],"pairs":[{}]}}}}"#,
+            kind, pairs
+        );
+        serde_json::from_str(&text).unwrap()
+    }
+
+    const BTC_USD: &str = r#"{"symbol":"BTC/USD","base":"BTC","quote":"USD","status":"online",
+        "qty_precision":8,"qty_increment":0.00000001,"price_precision":1,"cost_precision":5,
+        "marginable":true,"has_index":true,"cost_min":0.5,"tick_size":0.1,
+        "price_increment":0.1,"qty_min":0.0001}"#;
+
+    #[test]
+    fn test_snapshot_and_updates() {
+        let registry = InstrumentRegistry::default();
+        registry.apply(&message("snapshot", BTC_USD));
+        let pair = registry.pair("BTC/USD").unwrap();
+        assert_eq!(pair.qty_min, dec!(0.0001));
+        assert_eq!(
+            registry.precision("BTC/USD"),
+            Some(Precision { price: 1, qty: 8 })
+        );
+        assert_eq!(pair.round_price(dec!(42000.26)), dec!(42000.3));
+        assert_eq!(pair.round_qty(dec!(0.123456789)), dec!(0.12345678));
+        assert_eq!(pair.round_price(dec!(42000)).to_string(), "42000.0");
+        // Midpoints round away from zero, not to the even tick.
+        assert_eq!(pair.round_price(dec!(42000.25)), dec!(42000.3));
+        assert_eq!(pair.round_price(dec!(42000.35)), dec!(42000.4));
+
+        let halted = BTC_USD.replace(r#""status":"online""#, r#""status":"maintenance""#);
+        registry.apply(&message("update", &halted));
+        assert!(!registry.pair("BTC/USD").unwrap().is_online());
+        assert_eq!(registry.pairs().len(), 1);
+    }
+}
@@ -0,0 +1,330 @@
+use rust_decimal::Decimal;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use crate::book::OrderBook;
+pub use crate::protocol::level3::{Level3Action, Level3Event, Level3Order, Level3Wrapper};
+use crate::protocol::{
+    event::MessageType,
+    trades::{OrderbookEvent, PriceLevel},
+};
+
+/// An order resting in a `Level3Book`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestingOrder {
+    pub order_id: String,
+    pub price: Decimal,
+    pub qty: Decimal,
+    pub timestamp: String,
+}
+
+/// Where an order stands in its price level's queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueuePosition {
+    /// Orders ahead of it at the same price.
+    pub orders_ahead: usize,
+    /// Their combined quantity.
+    pub qty_ahead: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Bid,
+    Ask,
+}
+
+/// Local level 3 book for one symbol: every order, in time priority within
+/// its price level, kept in sync from `level3` messages.
+#[derive(Debug, Clone)]
+pub struct Level3Book {
+    symbol: String,
+    bids: BTreeMap<Reverse<Decimal>, Vec<RestingOrder>>,
+    asks: BTreeMap<Decimal, Vec<RestingOrder>>,
+    index: HashMap<String, (Side, Decimal)>,
+}
+
+impl Level3Book {
+    pub fn new(symbol: impl Into<String>) -> Self {
+        Self {
+            symbol: symbol.into(),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// A snapshot replaces the book. In an update each order is added,
+    /// modified or deleted as its `event` says; a modify at an unchanged
+    /// price keeps the order's place in the queue.
+    pub fn apply(&mut self, kind: MessageType, data: &Level3Event) {
+        if kind == MessageType::Snapshot {
+            self.bids.clear();
+            self.asks.clear();
+            self.index.clear();
+        }
+        for order in &data.bids {
+            self.apply_order(Side::Bid, order);
+        }
+        for order in &data.asks {
+            self.apply_order(Side::Ask, order);
+        }
+    }
+
+    pub fn order(&self, order_id: &str) -> Option<&RestingOrder> {
+        let (side, price) = *self.index.get(order_id)?;
+        self.queue(side, price)?
+            .iter()
+            .find(|order| order.order_id == order_id)
+    }
+
+    pub fn queue_position(&self, order_id: &str) -> Option<QueuePosition> {
+        let (side, price) = *self.index.get(order_id)?;
+        let queue = self.queue(side, price)?;
+        let orders_ahead = queue.iter().position(|order| order.order_id == order_id)?;
+        Some(QueuePosition {
+            orders_ahead,
+            qty_ahead: queue[..orders_ahead].iter().map(|order| order.qty).sum(),
+        })
+    }
+
+    /// Orders at `price` on either side, oldest first.
+    pub fn orders_at(&self, price: Decimal) -> &[RestingOrder] {
+        self.bids
+            .get(&Reverse(price))
+            .or_else(|| self.asks.get(&price))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Every bid, best price first, oldest first within a price.
+    pub fn bids(&self) -> impl Iterator<Item = &RestingOrder> + '_ {
+        self.bids.values().flatten()
+    }
+
+    /// Every ask, best price first, oldest first within a price.
+    pub fn asks(&self) -> impl Iterator<Item = &RestingOrder> + '_ {
+        self.asks.values().flatten()
+    }
+
+    /// Bids summed per price level, best first, as a level 2 book has them.
+    pub fn bid_levels(&self) -> Vec<PriceLevel> {
+        self.bids
+            .iter()
+            .map(|(Reverse(price), queue)| Self::level(*price, queue))
+            .collect()
+    }
+
+    /// Asks summed per price level, best first.
+    pub fn ask_levels(&self) -> Vec<PriceLevel> {
+        self.asks
+            .iter()
+            .map(|(price, queue)| Self::level(*price, queue))
+            .collect()
+    }
+
+    /// The aggregated book, `depth` levels per side.
+    pub fn to_order_book(&self, depth: u32) -> OrderBook {
+        let take = depth as usize;
+        let mut book = OrderBook::new(self.symbol.clone(), depth);
+        let levels = OrderbookEvent {
+            symbol: self.symbol.clone(),
+            bids: self.bid_levels().into_iter().take(take).collect(),
+            asks: self.ask_levels().into_iter().take(take).collect(),
+            checksum: 0,
+            timestamp: None,
+        };
+        book.apply(MessageType::Snapshot, &levels);
+        book
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    fn level(price: Decimal, queue: &[RestingOrder]) -> PriceLevel {
+        PriceLevel {
+            price,
+            qty: queue.iter().map(|order| order.qty).sum(),
+        }
+    }
+
+    fn queue(&self, side: Side, price: Decimal) -> Option<&Vec<RestingOrder>> {
+        match side {
+            Side::Bid => self.bids.get(&Reverse(price)),
+            Side::Ask => self.asks.get(&price),
+        }
+    }
+
+    fn queue_mut(&mut self, side: Side, price: Decimal) -> &mut Vec<RestingOrder> {
+        match side {
+            Side::Bid => self.bids.entry(Reverse(price)).or_default(),
+            Side::Ask => self.asks.entry(price).or_default(),
+        }
+    }
+
+    fn apply_order(&mut self, side: Side, order: &Level3Order) {
+        let price = order.limit_price;
+        if order.event == Some(Level3Action::Modify)
+            && self.index.get(&order.order_id) == Some(&(side, price))
+        {
+            if let Some(resting) = self
+                .queue_mut(side, price)
+                .iter_mut()
+                .find(|resting| resting.order_id == order.order_id)
+            {
+                resting.qty = order.order_qty;
+                resting.timestamp = order.timestamp.clone();
+            }
+            return;
+        }
+
+        self.remove(&order.order_id);
+        if order.event != Some(Level3Action::Delete) {
+            self.index.insert(order.order_id.clone(), (side, price));
+            self.queue_mut(side, price).push(RestingOrder {
+                order_id: order.order_id.clone(),
+                price,
+                qty: order.order_qty,
+                timestamp: order.timestamp.clone(),
+            });
+        }
+    }
+
+    fn remove(&mut self, order_id: &str) {
+        let Some((side, price)) = self.index.remove(order_id) else {
+            return;
+        };
+        let queue = self.queue_mut(side, price);
+        queue.retain(|order| order.order_id != order_id);
+        if queue.is_empty() {
+            match side {
+                Side::Bid => self.bids.remove(&Reverse(price)),
+                Side::Ask => self.asks.remove(&price),
+            };
+        }
+    }
+}
+
+/// Level 3 books for every `level3` subscription, updated by the session
+/// before each message is forwarded.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Level3Store {
+    books: Arc<Mutex<HashMap<String, Level3Book>>>,
+}
+
+impl Level3Store {
+    /// Updates for a symbol without a snapshot yet are ignored.
+    pub(crate) fn apply(&self, message: &Level3Wrapper) {
+        let mut books = self.books.lock().unwrap();
+        for data in &message.data {
+            if message.event_type == MessageType::Snapshot {
+                books.insert(data.symbol.clone(), Level3Book::new(data.symbol.clone()));
+            }
+            if let Some(book) = books.get_mut(&data.symbol) {
+                book.apply(message.event_type, data);
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, symbol: &str) -> Option<Level3Book> {
+        self.books.lock().unwrap().get(symbol).cloned()
+    }
+
+    pub(crate) fn remove(&self, symbol: &str) {
+        self.books.lock().unwrap().remove(symbol);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.books.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn order(event: Option<Level3Action>, id: &str, price: Decimal, qty: Decimal) -> Level3Order {
+        Level3Order {
+            event,
+            order_id: id.to_string(),
+            limit_price: price,
+            order_qty: qty,
+            timestamp: "2024-01-01T00:00:00.000000000Z".to_string(),
+        }
+    }
+
+    fn data(bids: Vec<Level3Order>, asks: Vec<Level3Order>) -> Level3Event {
+        Level3Event {
+            symbol: "BTC/USD".to_string(),
+            bids,
+            asks,
+            checksum: 0,
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_queue_position_and_aggregation() {
+        let mut book = Level3Book::new("BTC/USD");
+        book.apply(
+            MessageType::Snapshot,
+            &data(
+                vec![
+                    order(None, "A", dec!(100), dec!(1)),
+                    order(None, "B", dec!(100), dec!(2)),
+                    order(None, "C", dec!(100), dec!(3)),
+                    order(None, "D", dec!(99), dec!(4)),
+                ],
+                vec![order(None, "E", dec!(101), dec!(5))],
+            ),
+        );
+        assert_eq!(
+            book.queue_position("C"),
+            Some(QueuePosition {
+                orders_ahead: 2,
+                qty_ahead: dec!(3)
+            })
+        );
+
+        use Level3Action::*;
+        book.apply(
+            MessageType::Update,
+            &data(
+                vec![
+                    order(Some(Modify), "A", dec!(100), dec!(0.5)),
+                    order(Some(Delete), "B", dec!(100), dec!(2)),
+                    order(Some(Add), "F", dec!(100), dec!(7)),
+                ],
+                vec![order(Some(Delete), "E", dec!(101), dec!(5))],
+            ),
+        );
+        // A kept its place after the partial fill; F joined the back.
+        let ids: Vec<_> = book
+            .orders_at(dec!(100))
+            .iter()
+            .map(|o| o.order_id.as_str())
+            .collect();
+        assert_eq!(ids, ["A", "C", "F"]);
+        assert_eq!(book.queue_position("C").unwrap().qty_ahead, dec!(0.5));
+        assert!(book.ask_levels().is_empty());
+        assert_eq!(
+            book.bid_levels(),
+            vec![
+                PriceLevel {
+                    price: dec!(100),
+                    qty: dec!(10.5)
+                },
+                PriceLevel {
+                    price: dec!(99),
+                    qty: dec!(4)
+                },
+            ]
+        );
+        assert_eq!(book.to_order_book(1).top_bids(5).len(), 1);
+    }
+}
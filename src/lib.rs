@@ -45,14 +45,29 @@
 //! See the `examples/` directory for comprehensive usage examples.
 
 pub mod auth;
+pub mod balances;
+pub mod book;
+mod candle;
 pub mod client;
 pub mod config;
+#[cfg(not(target_arch = "wasm32"))]
+mod connection;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod dead_man_switch;
 pub mod error;
+pub mod executions;
+pub mod instrument;
+pub mod level3;
 pub mod metrics;
 pub mod operation;
 pub mod parser;
 pub mod rate_limit;
+mod registry;
+mod request;
+pub mod state;
 pub mod stream;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod token;
 pub mod types;
 #[cfg(target_arch = "wasm32")]
 pub mod wasm_ws;
@@ -61,11 +76,37 @@ pub mod wasm_ws;
 mod protocol;
 
 pub use auth::Auth;
+pub use balances::{AssetBalance, BalancesData, LedgerUpdate, WalletBalance};
+pub use book::{BookResync, OrderBook, Precision};
 pub use client::Client;
 pub use config::Config;
+#[cfg(not(target_arch = "wasm32"))]
+pub use dead_man_switch::DeadManSwitch;
 pub use error::{Error, Result};
+pub use executions::{ExecType, ExecutionReport, Fee, Liquidity, OrderStatus};
+pub use instrument::{AssetInfo, InstrumentRegistry, PairInfo};
+pub use level3::{Level3Book, QueuePosition, RestingOrder};
 pub use metrics::Metrics;
-pub use protocol::event::KrakenEvent;
+pub use parser::DecodeError;
+#[cfg(feature = "zero-copy")]
+pub use parser::ZeroCopyParser;
+#[cfg(feature = "zero-copy")]
+pub use protocol::borrowed::{BookRef, BorrowedEvent, ChannelData, TickerRef, TradeRef};
+pub use protocol::event::{KrakenEvent, MessageType};
+pub use protocol::ohlc::{OhlcEvent, OhlcWrapper};
+pub use protocol::trades::PriceLevel;
 pub use rate_limit::RateLimiter;
+pub use rust_decimal::Decimal;
+pub use state::ConnectionState;
 pub use stream::KrakenStream;
-pub use types::{AddOrderRequest, BatchOrderRequest, CancelOrderRequest};
+#[cfg(feature = "zero-copy")]
+pub use stream::RawStream;
+#[cfg(not(target_arch = "wasm32"))]
+pub use token::TokenProvider;
+pub use types::{
+    AddOrderRequest, AddOrderResult, AmendOrderRequest, AmendOrderResult, BatchCancelRequest,
+    BatchCancelResult, BatchOrderRequest, CancelAllOrdersAfterRequest, CancelAllOrdersAfterResult,
+    CancelAllRequest, CancelAllResult, CancelOrderRequest, CancelOrderResult, Channel,
+    EditOrderRequest, EditOrderResult, EventTrigger, FeePreference, OrderType, PriceType, Side,
+    StpType, SubscribeResult, Subscription, TimeInForce, TriggerReference, Triggers,
+};
@@ -1,12 +1,16 @@
 use instant::Instant;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub struct Metrics {
     pub msg_count: Arc<AtomicU64>,
     pub start_time: Instant,
     pub last_msg_time: Arc<AtomicU64>,
+    pub rtt_us: Arc<AtomicU64>,
+    pub decode_errors: Arc<AtomicU64>,
+    pub unknown_messages: Arc<AtomicU64>,
 }
 
 impl Default for Metrics {
@@ -15,6 +19,9 @@ impl Default for Metrics {
             msg_count: Arc::new(AtomicU64::new(0)),
             start_time: Instant::now(),
             last_msg_time: Arc::new(AtomicU64::new(0)),
+            rtt_us: Arc::new(AtomicU64::new(0)),
+            decode_errors: Arc::new(AtomicU64::new(0)),
+            unknown_messages: Arc::new(AtomicU64::new(0)),
         }
     }
 }
@@ -40,13 +47,42 @@ impl Metrics {
         let last = self.last_msg_time.load(Ordering::Relaxed);
         now.saturating_sub(last)
     }
+
+    pub fn record_rtt(&self, rtt: Duration) {
+        // Clamp to 1us so a measured round trip is never mistaken for "none yet".
+        let micros = (rtt.as_micros() as u64).max(1);
+        self.rtt_us.store(micros, Ordering::Relaxed);
+    }
+
+    /// Round trip of the most recent ping/pong exchange.
+    pub fn rtt(&self) -> Option<Duration> {
+        match self.rtt_us.load(Ordering::Relaxed) {
+            0 => None,
+            micros => Some(Duration::from_micros(micros)),
+        }
+    }
+
+    pub fn record_decode_error(&self) {
+        self.decode_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_unknown(&self) {
+        self.unknown_messages.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn decode_error_count(&self) -> u64 {
+        self.decode_errors.load(Ordering::Relaxed)
+    }
+
+    pub fn unknown_count(&self) -> u64 {
+        self.unknown_messages.load(Ordering::Relaxed)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::thread;
-    use std::time::Duration;
 
     #[test]
     fn test_metrics_default() {
@@ -71,4 +107,12 @@ mod tests {
         let rate = metrics.msg_per_sec();
         assert!(rate > 0.0);
     }
+
+    #[test]
+    fn test_record_rtt() {
+        let metrics = Metrics::default();
+        assert_eq!(metrics.rtt(), None);
+        metrics.record_rtt(Duration::from_millis(12));
+        assert_eq!(metrics.rtt(), Some(Duration::from_millis(12)));
+    }
 }
@@ -1,3 +1,19 @@
+pub mod subscribe_balances;
+pub mod subscribe_book;
+pub mod subscribe_executions;
+pub mod subscribe_instruments;
+pub mod subscribe_level3;
+pub mod subscribe_ohlc;
 pub mod subscribe_ticker;
+pub mod subscribe_trades;
+pub mod unsubscribe;
 
+pub use subscribe_balances::*;
+pub use subscribe_book::*;
+pub use subscribe_executions::*;
+pub use subscribe_instruments::*;
+pub use subscribe_level3::*;
+pub use subscribe_ohlc::*;
 pub use subscribe_ticker::*;
+pub use subscribe_trades::*;
+pub use unsubscribe::*;
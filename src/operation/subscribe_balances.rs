@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    types::{Channel, SubscribeInput, SubscribeResult},
+    Client, Result,
+};
+
+/// Subscribes to the account's balances and ledger entries. Authenticated:
+/// the socket must be `wss://ws-auth.kraken.com/v2` and a token is required.
+pub struct SubscribeBalancesFluentBuilder {
+    client: Client,
+    token: Option<String>,
+    snapshot: Option<bool>,
+    extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl SubscribeBalancesFluentBuilder {
+    pub(crate) fn new(client: Client) -> Self {
+        Self {
+            client,
+            token: None,
+            snapshot: None,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    /// Only needed when the client has no `TokenProvider` configured.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Start with every asset's balance (Kraken's default).
+    pub fn snapshot(mut self, snapshot: bool) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+
+    /// Any other subscription parameter, passed through as is.
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.extra.insert(name.into(), value.into());
+        self
+    }
+
+    pub async fn send(self) -> Result<Vec<SubscribeResult>> {
+        let mut input = SubscribeInput::subscribe(Channel::Balances, Vec::new());
+        input.params.token = self.token;
+        input.params.snapshot = self.snapshot;
+        input.params.extra = self.extra;
+        self.client.send_subscription(input).await
+    }
+}
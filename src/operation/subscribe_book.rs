@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    types::{SubscribeInput, SubscribeResult},
+    Client, Result,
+};
+
+pub struct SubscribeBookFluentBuilder {
+    client: Client,
+    symbols: Vec<String>,
+    depth: Option<u32>,
+    extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl SubscribeBookFluentBuilder {
+    pub(crate) fn new(client: Client) -> Self {
+        Self {
+            client,
+            symbols: Vec::new(),
+            depth: None,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    pub fn symbols(mut self, symbols: Vec<String>) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    pub fn symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbols.push(symbol.into());
+        self
+    }
+
+    /// Levels per side: 10 (the default), 25, 100, 500 or 1000.
+    pub fn depth(mut self, depth: u32) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Any other subscription parameter, passed through as is.
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.extra.insert(name.into(), value.into());
+        self
+    }
+
+    pub async fn send(self) -> Result<Vec<SubscribeResult>> {
+        let mut input = SubscribeInput::book(self.symbols, self.depth);
+        input.params.extra = self.extra;
+        self.client.send_subscription(input).await
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    types::{Channel, SubscribeInput, SubscribeResult},
+    Client, Result,
+};
+
+/// Subscribes to the account's order updates and fills. Authenticated: the
+/// socket must be `wss://ws-auth.kraken.com/v2` and a token is required.
+pub struct SubscribeExecutionsFluentBuilder {
+    client: Client,
+    token: Option<String>,
+    extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl SubscribeExecutionsFluentBuilder {
+    pub(crate) fn new(client: Client) -> Self {
+        Self {
+            client,
+            token: None,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    /// Only needed when the client has no `TokenProvider` configured.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Start with the open orders (Kraken's default).
+    pub fn snap_orders(self, snapshot: bool) -> Self {
+        self.param("snap_orders", snapshot)
+    }
+
+    /// Start with the most recent fills (off by default).
+    pub fn snap_trades(self, snapshot: bool) -> Self {
+        self.param("snap_trades", snapshot)
+    }
+
+    /// Also report `status` changes, such as an order becoming active.
+    pub fn order_status(self, enabled: bool) -> Self {
+        self.param("order_status", enabled)
+    }
+
+    /// Any other subscription parameter, passed through as is.
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.extra.insert(name.into(), value.into());
+        self
+    }
+
+    pub async fn send(self) -> Result<Vec<SubscribeResult>> {
+        let mut input = SubscribeInput::subscribe(Channel::Executions, Vec::new());
+        input.params.token = self.token;
+        input.params.extra = self.extra;
+        self.client.send_subscription(input).await
+    }
+}
@@ -0,0 +1,41 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    types::{SubscribeInput, SubscribeResult},
+    Client, Result,
+};
+
+/// Subscribes to reference data for every pair and asset; the channel takes
+/// no symbols. Messages keep `Client::instruments` up to date.
+pub struct SubscribeInstrumentsFluentBuilder {
+    client: Client,
+    snapshot: Option<bool>,
+    extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl SubscribeInstrumentsFluentBuilder {
+    pub(crate) fn new(client: Client) -> Self {
+        Self {
+            client,
+            snapshot: None,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    pub fn snapshot(mut self, snapshot: bool) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+
+    /// Any other subscription parameter, passed through as is.
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.extra.insert(name.into(), value.into());
+        self
+    }
+
+    pub async fn send(self) -> Result<Vec<SubscribeResult>> {
+        let mut input = SubscribeInput::instruments(self.snapshot);
+        input.params.extra = self.extra;
+        self.client.send_subscription(input).await
+    }
+}
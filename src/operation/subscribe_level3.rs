@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    types::{SubscribeInput, SubscribeResult},
+    Client, Result,
+};
+
+/// Subscribes to every individual order in the book. `level3` is an
+/// authenticated channel served from its own endpoint,
+/// `wss://ws-l3.kraken.com/v2`, and needs a WebSocket token.
+pub struct SubscribeLevel3FluentBuilder {
+    client: Client,
+    symbols: Vec<String>,
+    depth: Option<u32>,
+    token: Option<String>,
+    extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl SubscribeLevel3FluentBuilder {
+    pub(crate) fn new(client: Client) -> Self {
+        Self {
+            client,
+            symbols: Vec::new(),
+            depth: None,
+            token: None,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    pub fn symbols(mut self, symbols: Vec<String>) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    pub fn symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbols.push(symbol.into());
+        self
+    }
+
+    /// Price levels per side: 10 (the default), 100 or 1000.
+    pub fn depth(mut self, depth: u32) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Only needed when the client has no `TokenProvider` configured.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Any other subscription parameter, passed through as is.
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.extra.insert(name.into(), value.into());
+        self
+    }
+
+    pub async fn send(self) -> Result<Vec<SubscribeResult>> {
+        let mut input = SubscribeInput::level3(self.symbols, self.depth, self.token);
+        input.params.extra = self.extra;
+        self.client.send_subscription(input).await
+    }
+}
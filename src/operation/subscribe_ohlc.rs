@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    types::{SubscribeInput, SubscribeResult},
+    Client, Result,
+};
+
+pub struct SubscribeOhlcFluentBuilder {
+    client: Client,
+    symbols: Vec<String>,
+    interval: Option<u32>,
+    snapshot: Option<bool>,
+    extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl SubscribeOhlcFluentBuilder {
+    pub(crate) fn new(client: Client) -> Self {
+        Self {
+            client,
+            symbols: Vec::new(),
+            interval: None,
+            snapshot: None,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    pub fn symbols(mut self, symbols: Vec<String>) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    pub fn symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbols.push(symbol.into());
+        self
+    }
+
+    /// Candle length in minutes: 1 (the default), 5, 15, 30, 60, 240, 1440,
+    /// 10080 or 21600.
+    pub fn interval(mut self, minutes: u32) -> Self {
+        self.interval = Some(minutes);
+        self
+    }
+
+    /// Whether Kraken starts with recent candles (the default).
+    pub fn snapshot(mut self, snapshot: bool) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+
+    /// Any other subscription parameter, passed through as is.
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.extra.insert(name.into(), value.into());
+        self
+    }
+
+    pub async fn send(self) -> Result<Vec<SubscribeResult>> {
+        let mut input = SubscribeInput::ohlc(self.symbols, self.interval, self.snapshot);
+        input.params.extra = self.extra;
+        self.client.send_subscription(input).await
+    }
+}
@@ -1,8 +1,16 @@
-use crate::{types::SubscribeInput, Client, Result};
+use std::collections::BTreeMap;
+
+use crate::{
+    types::{EventTrigger, SubscribeInput, SubscribeResult},
+    Client, Result,
+};
 
 pub struct SubscribeTickerFluentBuilder {
     client: Client,
     symbols: Vec<String>,
+    event_trigger: Option<EventTrigger>,
+    snapshot: Option<bool>,
+    extra: BTreeMap<String, serde_json::Value>,
 }
 
 impl SubscribeTickerFluentBuilder {
@@ -10,6 +18,9 @@ impl SubscribeTickerFluentBuilder {
         Self {
             client,
             symbols: Vec::new(),
+            event_trigger: None,
+            snapshot: None,
+            extra: BTreeMap::new(),
         }
     }
 
@@ -23,8 +34,29 @@ impl SubscribeTickerFluentBuilder {
         self
     }
 
-    pub async fn send(self) -> Result<()> {
-        let input = SubscribeInput::ticker(self.symbols);
+    /// `Bbo` for an update on every best bid/offer change, `Trades` (the
+    /// default) for one per trade.
+    pub fn event_trigger(mut self, trigger: EventTrigger) -> Self {
+        self.event_trigger = Some(trigger);
+        self
+    }
+
+    pub fn snapshot(mut self, snapshot: bool) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+
+    /// Any other subscription parameter, passed through as is.
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.extra.insert(name.into(), value.into());
+        self
+    }
+
+    pub async fn send(self) -> Result<Vec<SubscribeResult>> {
+        let mut input = SubscribeInput::ticker(self.symbols);
+        input.params.event_trigger = self.event_trigger;
+        input.params.snapshot = self.snapshot;
+        input.params.extra = self.extra;
         self.client.send_subscription(input).await
     }
 }
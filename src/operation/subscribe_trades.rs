@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    types::{SubscribeInput, SubscribeResult},
+    Client, Result,
+};
+
+pub struct SubscribeTradesFluentBuilder {
+    client: Client,
+    symbols: Vec<String>,
+    snapshot: Option<bool>,
+    extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl SubscribeTradesFluentBuilder {
+    pub(crate) fn new(client: Client) -> Self {
+        Self {
+            client,
+            symbols: Vec::new(),
+            snapshot: None,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    pub fn symbols(mut self, symbols: Vec<String>) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    pub fn symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbols.push(symbol.into());
+        self
+    }
+
+    /// Whether Kraken starts with the most recent trades (the default) or
+    /// only sends new ones.
+    pub fn snapshot(mut self, snapshot: bool) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+
+    /// Any other subscription parameter, passed through as is.
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.extra.insert(name.into(), value.into());
+        self
+    }
+
+    pub async fn send(self) -> Result<Vec<SubscribeResult>> {
+        let mut input = SubscribeInput::trades(self.symbols, self.snapshot);
+        input.params.extra = self.extra;
+        self.client.send_subscription(input).await
+    }
+}
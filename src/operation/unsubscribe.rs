@@ -0,0 +1,45 @@
+use crate::{
+    types::{Channel, SubscribeInput, SubscribeResult},
+    Client, Result,
+};
+
+pub struct UnsubscribeFluentBuilder {
+    client: Client,
+    channel: Channel,
+    symbols: Vec<String>,
+    token: Option<String>,
+}
+
+impl UnsubscribeFluentBuilder {
+    pub(crate) fn new(client: Client, channel: Channel) -> Self {
+        Self {
+            client,
+            channel,
+            symbols: Vec::new(),
+            token: None,
+        }
+    }
+
+    pub fn symbols(mut self, symbols: Vec<String>) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    pub fn symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbols.push(symbol.into());
+        self
+    }
+
+    /// For private channels; only needed when the client has no
+    /// `TokenProvider` configured.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub async fn send(self) -> Result<Vec<SubscribeResult>> {
+        let mut input = SubscribeInput::unsubscribe(self.channel, self.symbols);
+        input.params.token = self.token;
+        self.client.send_unsubscription(input).await
+    }
+}
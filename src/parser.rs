@@ -1,15 +1,145 @@
+use serde::{de::DeserializeOwned, Deserialize};
+use std::borrow::Cow;
+
+#[cfg(feature = "zero-copy")]
+use crate::protocol::borrowed::BorrowedEvent;
 use crate::protocol::event::KrakenEvent;
-use crate::Result;
+use crate::{Error, Result};
+
+/// The fields that decide which `KrakenEvent` a message becomes.
+#[derive(Deserialize)]
+struct Envelope<'a> {
+    #[serde(borrow)]
+    channel: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    method: Option<Cow<'a, str>>,
+    #[cfg(all(feature = "zero-copy", not(target_arch = "wasm32")))]
+    req_id: Option<u64>,
+}
+
+pub(crate) enum Route {
+    Ticker,
+    Trade,
+    Book,
+    Ohlc,
+    Instrument,
+    Level3,
+    Executions,
+    Balances,
+    Heartbeat,
+    Status,
+    Pong,
+    Order,
+    Response,
+    Unknown,
+}
+
+impl Route {
+    fn of(envelope: &Envelope) -> Self {
+        match (envelope.channel.as_deref(), envelope.method.as_deref()) {
+            (Some("ticker"), _) => Route::Ticker,
+            (Some("trade"), _) => Route::Trade,
+            (Some("book"), _) => Route::Book,
+            (Some("ohlc"), _) => Route::Ohlc,
+            (Some("instrument"), _) => Route::Instrument,
+            (Some("level3"), _) => Route::Level3,
+            (Some("executions"), _) => Route::Executions,
+            (Some("balances"), _) => Route::Balances,
+            (Some("heartbeat"), _) => Route::Heartbeat,
+            (Some("status"), _) => Route::Status,
+            (_, Some("pong")) => Route::Pong,
+            (_, Some(method)) if is_order_method(method) => Route::Order,
+            (_, Some(_)) => Route::Response,
+            _ => Route::Unknown,
+        }
+    }
+}
+
+/// Methods that place, change or cancel orders; their replies all become
+/// `KrakenEvent::Order`.
+fn is_order_method(method: &str) -> bool {
+    matches!(
+        method,
+        "add_order"
+            | "amend_order"
+            | "edit_order"
+            | "cancel_order"
+            | "cancel_all"
+            | "cancel_all_orders_after"
+            | "batch_add"
+            | "batch_cancel"
+    )
+}
+
+/// Something a routed message can be deserialized from.
+trait Source {
+    fn decode<T: DeserializeOwned>(self) -> Result<T>;
+}
+
+impl Source for &str {
+    fn decode<T: DeserializeOwned>(self) -> Result<T> {
+        serde_json::from_str(self).map_err(|e| Error::Json(Box::new(e)))
+    }
+}
+
+impl Source for serde_json::Value {
+    fn decode<T: DeserializeOwned>(self) -> Result<T> {
+        serde_json::from_value(self).map_err(|e| Error::Json(Box::new(e)))
+    }
+}
+
+#[cfg(feature = "zero-copy")]
+struct Simd<'a> {
+    data: &'a mut [u8],
+    buffers: &'a mut simd_json::Buffers,
+}
+
+#[cfg(feature = "zero-copy")]
+impl Source for Simd<'_> {
+    fn decode<T: DeserializeOwned>(self) -> Result<T> {
+        Ok(simd_json::serde::from_slice_with_buffers(
+            self.data,
+            self.buffers,
+        )?)
+    }
+}
+
+/// A known channel whose payload no longer matches its type is an error,
+/// not an `Unknown` event, so schema drift shows up in `decode_errors`.
+fn dispatch(route: Route, source: impl Source) -> Result<KrakenEvent> {
+    Ok(match route {
+        Route::Ticker => KrakenEvent::Ticker(source.decode()?),
+        Route::Trade => KrakenEvent::Trade(source.decode()?),
+        Route::Book => KrakenEvent::Orderbook(source.decode()?),
+        Route::Ohlc => KrakenEvent::Ohlc(source.decode()?),
+        Route::Instrument => KrakenEvent::Instrument(source.decode()?),
+        Route::Level3 => KrakenEvent::Level3(source.decode()?),
+        Route::Executions => KrakenEvent::Executions(source.decode()?),
+        Route::Balances => KrakenEvent::Balances(source.decode()?),
+        Route::Heartbeat => KrakenEvent::Heartbeat(source.decode()?),
+        Route::Status => KrakenEvent::Status(source.decode()?),
+        Route::Pong => KrakenEvent::Pong(source.decode()?),
+        Route::Order => KrakenEvent::Order(source.decode()?),
+        Route::Response => KrakenEvent::Response(source.decode()?),
+        Route::Unknown => KrakenEvent::Unknown(source.decode()?),
+    })
+}
+
+/// A message the SDK received but could not turn into a `KrakenEvent`.
+#[derive(Debug, Clone)]
+pub struct DecodeError {
+    pub error: String,
+    pub raw: String,
+}
 
 pub struct Parser;
 
 impl Parser {
+    /// Decodes with simd-json under `zero-copy`; see `ZeroCopyParser` for
+    /// how that affects decimals.
     #[cfg(feature = "zero-copy")]
     pub fn parse_zero_copy(data: &mut [u8]) -> Result<KrakenEvent> {
-        let value = simd_json::to_borrowed_value(data)?;
-        let json_value: serde_json::Value =
-            serde_json::to_value(value).map_err(|e| crate::Error::Json(Box::new(e)))?;
-        serde_json::from_value(json_value).map_err(|e| crate::Error::Json(Box::new(e)))
+        ZeroCopyParser::default().parse_owned(data)
     }
 
     #[cfg(not(feature = "zero-copy"))]
@@ -18,6 +148,246 @@ impl Parser {
     }
 
     pub fn parse_standard(text: &str) -> Result<KrakenEvent> {
-        serde_json::from_str(text).map_err(|e| crate::Error::Json(Box::new(e)))
+        let envelope: Envelope =
+            serde_json::from_str(text).map_err(|e| Error::Json(Box::new(e)))?;
+        dispatch(Route::of(&envelope), text)
+    }
+
+    pub fn parse_value(value: serde_json::Value) -> Result<KrakenEvent> {
+        let envelope = Envelope::deserialize(&value).map_err(|e| Error::Json(Box::new(e)))?;
+        dispatch(Route::of(&envelope), value)
+    }
+
+    #[cfg(all(feature = "zero-copy", not(target_arch = "wasm32")))]
+    /// Routes a frame without decoding its payload, for callers that pass
+    /// the raw bytes on. Returns the route and the `req_id`, if any.
+    /// Market data is recognised from its leading `channel` key and
+    /// returns `None` without being parsed, since its consumer parses it.
+    pub(crate) fn peek(data: &[u8]) -> Option<(Route, Option<u64>)> {
+        if let Some(channel) = Self::leading_channel(data) {
+            if !matches!(channel, b"instrument" | b"executions" | b"balances") {
+                return None;
+            }
+        }
+        let envelope: Envelope = serde_json::from_slice(data).ok()?;
+        Some((Route::of(&envelope), envelope.req_id))
+    }
+
+    /// The channel of a frame that starts with it, as every channel message
+    /// from Kraken does. Anything else, including whitespace before the
+    /// key, is left to a full parse.
+    #[cfg(all(feature = "zero-copy", not(target_arch = "wasm32")))]
+    fn leading_channel(data: &[u8]) -> Option<&[u8]> {
+        let rest = data.strip_prefix(br#"{"channel":""#)?;
+        let end = rest.iter().position(|&byte| byte == b'"')?;
+        Some(&rest[..end])
+    }
+}
+
+/// Parses frames in place with simd-json, reusing its scratch buffers
+/// between calls. Ticker, trade and book messages borrow their strings from
+/// the frame instead of allocating.
+///
+/// simd-json reads every number as an `f64`, so decimals decoded here are
+/// the shortest form of that float: the exchange's digits for up to 15
+/// significant figures, but without trailing zeros. `Parser::parse_standard`
+/// keeps the exact text.
+///
+/// Borrowing needs `channel` ahead of `data`, as in every frame Kraken
+/// sends; a message with them the other way round is decoded into the
+/// owned type and returned as `BorrowedEvent::Other`.
+#[cfg(feature = "zero-copy")]
+#[derive(Default)]
+pub struct ZeroCopyParser {
+    buffers: simd_json::Buffers,
+}
+
+#[cfg(feature = "zero-copy")]
+impl ZeroCopyParser {
+    /// The frame is rewritten during parsing and must not be reused.
+    pub fn parse<'a>(&mut self, data: &'a mut [u8]) -> Result<BorrowedEvent<'a>> {
+        Ok(simd_json::serde::from_slice_with_buffers(
+            data,
+            &mut self.buffers,
+        )?)
+    }
+
+    /// Like `parse`, but always yields the owned event type.
+    pub fn parse_owned(&mut self, data: &mut [u8]) -> Result<KrakenEvent> {
+        let route = Self::route(data)?;
+        let buffers = &mut self.buffers;
+        dispatch(route, Simd { data, buffers })
+    }
+
+    fn route(data: &[u8]) -> Result<Route> {
+        let envelope: Envelope =
+            serde_json::from_slice(data).map_err(|e| Error::Json(Box::new(e)))?;
+        Ok(Route::of(&envelope))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::event::MessageType;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_routes_by_channel() {
+        let book = r#"{"channel":"book","type":"snapshot","data":[{"symbol":"BTC/USD",
+            "bids":[{"price":42000.1,"qty":0.5}],"asks":[{"price":42000.2,"qty":1.0}],
+            "checksum":2439117997}]}"#;
+        match Parser::parse_standard(book).unwrap() {
+            KrakenEvent::Orderbook(book) => {
+                assert_eq!(book.event_type, MessageType::Snapshot);
+                assert_eq!(book.data[0].bids[0].price, dec!(42000.1));
+                assert_eq!(book.data[0].bids[0].qty, dec!(0.5));
+            }
+            other => panic!("expected book, got {:?}", other),
+        }
+
+        let heartbeat = Parser::parse_standard(r#"{"channel":"heartbeat"}"#).unwrap();
+        assert!(matches!(heartbeat, KrakenEvent::Heartbeat(_)));
+    }
+
+    #[test]
+    fn test_routes_by_method() {
+        let ack = r#"{"method":"subscribe","req_id":1,"success":true,
+            "result":{"channel":"ticker","symbol":"BTC/USD"},
+            "time_in":"2024-01-01T00:00:00.000000Z","time_out":"2024-01-01T00:00:00.000100Z"}"#;
+        assert!(matches!(
+            Parser::parse_standard(ack).unwrap(),
+            KrakenEvent::Response(_)
+        ));
+
+        for method in [
+            "add_order",
+            "amend_order",
+            "edit_order",
+            "cancel_order",
+            "cancel_all",
+            "cancel_all_orders_after",
+            "batch_add",
+            "batch_cancel",
+        ] {
+            let reply =
+                format!(r#"{{"method":"{method}","req_id":3,"success":true,"result":{{}}}}"#);
+            match Parser::parse_standard(&reply).unwrap() {
+                KrakenEvent::Order(response) => assert_eq!(response.method, method),
+                other => panic!("expected order reply for {method}, got {:?}", other),
+            }
+        }
+
+        let pong = r#"{"method":"pong","req_id":2,
+            "time_in":"2024-01-01T00:00:00.000000Z","time_out":"2024-01-01T00:00:00.000100Z"}"#;
+        assert!(matches!(
+            Parser::parse_standard(pong).unwrap(),
+            KrakenEvent::Pong(_)
+        ));
+    }
+
+    #[test]
+    fn test_known_channel_with_bad_payload_is_an_error() {
+        let drifted = r#"{"channel":"ticker","type":"update","data":[{"symbol":"BTC/USD"}]}"#;
+        assert!(Parser::parse_standard(drifted).is_err());
+
+        let unknown = r#"{"channel":"future","type":"update","data":[]}"#;
+        assert!(matches!(
+            Parser::parse_standard(unknown).unwrap(),
+            KrakenEvent::Unknown(_)
+        ));
+    }
+
+    #[cfg(feature = "zero-copy")]
+    #[test]
+    fn test_zero_copy_borrows_from_frame() {
+        let mut parser = ZeroCopyParser::default();
+        let mut frame = br#"{"channel":"trade","type":"update","data":[{"symbol":"BTC/USD",
+            "side":"buy","price":42000.1,"qty":0.01,"ord_type":"market","trade_id":7,
+            "timestamp":"2024-01-01T00:00:00.000000Z"}]}"#
+            .to_vec();
+        match parser.parse(&mut frame).unwrap() {
+            BorrowedEvent::Trade(trades) => {
+                assert_eq!(trades.data[0].symbol, "BTC/USD");
+                assert!(!trades.data.spilled());
+            }
+            other => panic!("expected trade, got {:?}", other),
+        }
+
+        let mut heartbeat = br#"{"channel":"heartbeat"}"#.to_vec();
+        assert!(matches!(
+            parser.parse(&mut heartbeat).unwrap(),
+            BorrowedEvent::Other(KrakenEvent::Heartbeat(_))
+        ));
+    }
+
+    #[cfg(feature = "zero-copy")]
+    #[test]
+    fn test_zero_copy_borrows_in_kraken_key_order() {
+        let mut parser = ZeroCopyParser::default();
+        let mut ticker = br#"{"channel":"ticker","type":"snapshot","data":[{"symbol":"BTC/USD",
+            "bid":42000.1,"bid_qty":0.5,"ask":42000.2,"ask_qty":1.0,"last":42000.1,
+            "volume":1234.5,"vwap":41000.0,"low":40000.0,"high":43000.0,"change":100.0,
+            "change_pct":0.24}]}"#
+            .to_vec();
+        match parser.parse(&mut ticker).unwrap() {
+            BorrowedEvent::Ticker(ticker) => {
+                assert_eq!(ticker.event_type, MessageType::Snapshot);
+                assert_eq!(ticker.data[0].symbol, "BTC/USD");
+            }
+            other => panic!("expected ticker, got {:?}", other),
+        }
+
+        let mut book = br#"{"channel":"book","type":"update","data":[{"symbol":"BTC/USD",
+            "bids":[{"price":42000.1,"qty":0.5}],"asks":[],"checksum":2439117997,
+            "timestamp":"2024-01-01T00:00:00.000000Z"}]}"#
+            .to_vec();
+        match parser.parse(&mut book).unwrap() {
+            BorrowedEvent::Orderbook(book) => {
+                assert_eq!(book.event_type, MessageType::Update);
+                assert_eq!(book.data[0].bids[0].price, dec!(42000.1));
+            }
+            other => panic!("expected book, got {:?}", other),
+        }
+
+        // Keys ahead of `channel` do not force the owned path.
+        let mut reordered = br#"{"type":"update","channel":"book","data":[{"symbol":"BTC/USD",
+            "bids":[],"asks":[],"checksum":0}]}"#
+            .to_vec();
+        match parser.parse(&mut reordered).unwrap() {
+            BorrowedEvent::Orderbook(book) => assert_eq!(book.event_type, MessageType::Update),
+            other => panic!("expected book, got {:?}", other),
+        }
+
+        let mut data_first = br#"{"data":[{"symbol":"BTC/USD","bids":[],"asks":[],
+            "checksum":0}],"type":"update","channel":"book"}"#
+            .to_vec();
+        assert!(matches!(
+            parser.parse(&mut data_first).unwrap(),
+            BorrowedEvent::Other(KrakenEvent::Orderbook(_))
+        ));
+    }
+
+    #[cfg(all(feature = "zero-copy", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_peek_skips_market_data() {
+        let ticker = br#"{"channel":"ticker","type":"update","data":[{"symbol":"BTC/USD"}]}"#;
+        assert_eq!(Parser::leading_channel(ticker), Some(&b"ticker"[..]));
+        assert!(Parser::peek(ticker).is_none());
+
+        let balances = br#"{"channel":"balances","type":"update","data":[]}"#;
+        assert!(matches!(
+            Parser::peek(balances),
+            Some((Route::Balances, None))
+        ));
+
+        // A channel inside an acknowledgement's result does not count.
+        let ack = br#"{"method":"subscribe","req_id":4,"success":true,
+            "result":{"channel":"book","symbol":"BTC/USD"}}"#;
+        assert_eq!(Parser::leading_channel(ack), None);
+        assert!(matches!(
+            Parser::peek(ack),
+            Some((Route::Response, Some(4)))
+        ));
     }
 }
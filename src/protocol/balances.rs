@@ -0,0 +1,92 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer};
+
+use super::event::MessageType;
+
+/// Snapshots and updates on `balances` carry different records, so the
+/// message type decides how `data` is read.
+#[derive(Debug)]
+pub struct BalancesWrapper {
+    pub channel: String,
+    pub event_type: MessageType,
+    pub data: BalancesData,
+    pub sequence: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BalancesData {
+    /// Every asset's balance, per wallet.
+    Snapshot(Vec<AssetBalance>),
+    /// Ledger entries that changed a balance since.
+    Update(Vec<LedgerUpdate>),
+}
+
+impl<'de> Deserialize<'de> for BalancesWrapper {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Raw {
+            channel: String,
+            #[serde(rename = "type")]
+            event_type: MessageType,
+            data: serde_json::Value,
+            sequence: Option<u64>,
+        }
+
+        let raw = Raw::deserialize(deserializer)?;
+        let data = match raw.event_type {
+            MessageType::Snapshot => serde_json::from_value(raw.data).map(BalancesData::Snapshot),
+            MessageType::Update => serde_json::from_value(raw.data).map(BalancesData::Update),
+        }
+        .map_err(serde::de::Error::custom)?;
+        Ok(Self {
+            channel: raw.channel,
+            event_type: raw.event_type,
+            data,
+            sequence: raw.sequence,
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct AssetBalance {
+    pub asset: String,
+    pub asset_class: Option<String>,
+    /// Total over every wallet.
+    #[serde(with = "crate::types::decimal::number")]
+    pub balance: Decimal,
+    #[serde(default)]
+    pub wallets: Vec<WalletBalance>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct WalletBalance {
+    /// `spot`, `earn`, ...
+    #[serde(rename = "type")]
+    pub wallet_type: String,
+    pub id: String,
+    #[serde(with = "crate::types::decimal::number")]
+    pub balance: Decimal,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct LedgerUpdate {
+    pub ledger_id: String,
+    pub ref_id: Option<String>,
+    pub timestamp: String,
+    /// `trade`, `deposit`, `withdrawal`, `transfer`, `staking`, ...
+    #[serde(rename = "type")]
+    pub ledger_type: String,
+    pub subtype: Option<String>,
+    pub category: Option<String>,
+    pub asset: String,
+    pub asset_class: Option<String>,
+    pub wallet_type: String,
+    pub wallet_id: String,
+    #[serde(with = "crate::types::decimal::number")]
+    pub amount: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub fee: Decimal,
+    /// The wallet's balance after this entry.
+    #[serde(with = "crate::types::decimal::number")]
+    pub balance: Decimal,
+}
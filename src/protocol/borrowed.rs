@@ -0,0 +1,158 @@
+use rust_decimal::Decimal;
+use serde::de::{self, Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde::Deserialize;
+use smallvec::SmallVec;
+use std::fmt;
+use std::marker::PhantomData;
+
+use super::event::{KrakenEvent, MessageType};
+use super::trades::PriceLevel;
+
+/// Book sides sized for Kraken's default depth of 10, so ordinary updates
+/// stay inline.
+pub type Levels = SmallVec<[PriceLevel; 10]>;
+
+/// Hot-path market data borrowed from the frame it was parsed from.
+// Kept unboxed: boxing the book variant would bring back an allocation per message.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum BorrowedEvent<'a> {
+    Ticker(ChannelData<'a, TickerRef<'a>>),
+    Trade(ChannelData<'a, TradeRef<'a>>),
+    Orderbook(ChannelData<'a, BookRef<'a>>),
+    /// Anything else, decoded into the owned event type. Ticker, trade and
+    /// book messages only end up here if `data` comes before `channel`.
+    Other(KrakenEvent),
+}
+
+#[derive(Debug)]
+pub struct ChannelData<'a, T> {
+    pub channel: &'a str,
+    pub event_type: MessageType,
+    pub data: SmallVec<[T; 1]>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TickerRef<'a> {
+    pub symbol: &'a str,
+    #[serde(with = "crate::types::decimal::number")]
+    pub bid: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub bid_qty: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub ask: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub ask_qty: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub last: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub volume: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub vwap: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub low: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub high: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub change: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub change_pct: Decimal,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TradeRef<'a> {
+    pub symbol: &'a str,
+    pub side: &'a str,
+    #[serde(with = "crate::types::decimal::number")]
+    pub qty: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub price: Decimal,
+    pub ord_type: &'a str,
+    pub trade_id: u64,
+    pub timestamp: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BookRef<'a> {
+    pub symbol: &'a str,
+    pub bids: Levels,
+    pub asks: Levels,
+    pub checksum: u32,
+    pub timestamp: Option<&'a str>,
+}
+
+// Hot-path messages are routed and decoded in a single pass as long as
+// `channel` comes before `data`, as it does in everything Kraken sends.
+// Keys seen before `channel` are kept, and anything that is not ticker,
+// trade or book is collected and handed to `Parser`.
+impl<'de: 'a, 'a> Deserialize<'de> for BorrowedEvent<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(EventVisitor(PhantomData))
+    }
+}
+
+struct EventVisitor<'a>(PhantomData<&'a ()>);
+
+impl<'de: 'a, 'a> Visitor<'de> for EventVisitor<'a> {
+    type Value = BorrowedEvent<'a>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Kraken message object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut fields = serde_json::Map::new();
+        while let Some(key) = map.next_key::<&'de str>()? {
+            // Once `data` has been collected it can no longer be borrowed.
+            if key != "channel" || fields.contains_key("data") {
+                fields.insert(key.to_string(), map.next_value()?);
+                continue;
+            }
+            let channel: &'de str = map.next_value()?;
+            let event_type = fields
+                .get("type")
+                .map(MessageType::deserialize)
+                .transpose()
+                .map_err(de::Error::custom)?;
+            match channel {
+                "ticker" => {
+                    return channel_data(channel, event_type, map).map(BorrowedEvent::Ticker)
+                }
+                "trade" => return channel_data(channel, event_type, map).map(BorrowedEvent::Trade),
+                "book" => {
+                    return channel_data(channel, event_type, map).map(BorrowedEvent::Orderbook)
+                }
+                _ => fields.insert(key.to_string(), channel.into()),
+            };
+        }
+        crate::parser::Parser::parse_value(fields.into())
+            .map(BorrowedEvent::Other)
+            .map_err(de::Error::custom)
+    }
+}
+
+fn channel_data<'de, T, A>(
+    channel: &'de str,
+    mut event_type: Option<MessageType>,
+    mut map: A,
+) -> Result<ChannelData<'de, T>, A::Error>
+where
+    T: Deserialize<'de>,
+    A: MapAccess<'de>,
+{
+    let mut data = None;
+    while let Some(key) = map.next_key::<&'de str>()? {
+        match key {
+            "type" => event_type = Some(map.next_value()?),
+            "data" => data = Some(map.next_value()?),
+            _ => {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+    }
+    Ok(ChannelData {
+        channel,
+        event_type: event_type.ok_or_else(|| de::Error::missing_field("type"))?,
+        data: data.ok_or_else(|| de::Error::missing_field("data"))?,
+    })
+}
@@ -1,41 +1,96 @@
-use serde::Deserialize;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer};
 
 pub use super::trades::{OrderbookWrapper, TradeWrapper};
+use super::{
+    balances::BalancesWrapper,
+    executions::ExecutionsWrapper,
+    instrument::InstrumentWrapper,
+    level3::Level3Wrapper,
+    ohlc::{OhlcEvent, OhlcWrapper},
+    response::MethodResponse,
+};
+use crate::book::BookResync;
 
-#[derive(Deserialize, Debug)]
-#[serde(untagged)]
+/// Decoded by `Parser`, which routes on the message's `channel` or `method`
+/// instead of trying each variant in turn.
+#[derive(Debug)]
 pub enum KrakenEvent {
     Ticker(TickerWrapper),
     Trade(TradeWrapper),
     Orderbook(OrderbookWrapper),
+    Ohlc(OhlcWrapper),
+    Instrument(InstrumentWrapper),
+    Level3(Level3Wrapper),
+    Executions(ExecutionsWrapper),
+    Balances(BalancesWrapper),
     Heartbeat(HeartbeatEvent),
     Pong(PongEvent),
     Status(StatusEvent),
-    Order(super::order::OrderResponse),
+    /// Reply to an order method: `add_order`, `amend_order`, `edit_order`,
+    /// `cancel_order`, `cancel_all`, `cancel_all_orders_after`, `batch_add`
+    /// or `batch_cancel`.
+    Order(MethodResponse),
+    /// Acknowledgement of any other request, such as `subscribe`.
+    Response(MethodResponse),
+    /// A message on a channel or method this SDK does not know yet.
+    Unknown(serde_json::Value),
+    /// Raised by the client, not Kraken: a local book failed its checksum
+    /// and is being rebuilt from a fresh snapshot.
+    BookResync(BookResync),
+    /// Raised by the client: the final state of a candle, sent once the
+    /// next interval's first update arrives.
+    CandleClosed(OhlcEvent),
+}
+
+impl<'de> Deserialize<'de> for KrakenEvent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        crate::parser::Parser::parse_value(value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Whether a channel message carries the full state or a change to it.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageType {
+    Snapshot,
+    Update,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct TickerWrapper {
     pub channel: String,
     #[serde(rename = "type")]
-    pub event_type: String,
+    pub event_type: MessageType,
     pub data: Vec<TickerEvent>,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct TickerEvent {
     pub symbol: String,
-    pub bid: f64,
-    pub bid_qty: f64,
-    pub ask: f64,
-    pub ask_qty: f64,
-    pub last: f64,
-    pub volume: f64,
-    pub vwap: f64,
-    pub low: f64,
-    pub high: f64,
-    pub change: f64,
-    pub change_pct: f64,
+    #[serde(with = "crate::types::decimal::number")]
+    pub bid: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub bid_qty: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub ask: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub ask_qty: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub last: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub volume: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub vwap: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub low: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub high: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub change: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub change_pct: Decimal,
 }
 
 #[derive(Deserialize, Debug)]
@@ -0,0 +1,112 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use super::event::MessageType;
+use crate::types::{OrderType, Side, TimeInForce};
+
+#[derive(Deserialize, Debug)]
+pub struct ExecutionsWrapper {
+    pub channel: String,
+    #[serde(rename = "type")]
+    pub event_type: MessageType,
+    pub data: Vec<ExecutionReport>,
+    pub sequence: Option<u64>,
+}
+
+/// One order lifecycle event or fill. Updates only carry the fields that
+/// changed, so everything past the identifying fields is optional.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ExecutionReport {
+    pub order_id: String,
+    pub exec_type: ExecType,
+    pub timestamp: String,
+    pub order_status: Option<OrderStatus>,
+    pub exec_id: Option<String>,
+    pub trade_id: Option<u64>,
+    pub cl_ord_id: Option<String>,
+    pub order_userref: Option<i64>,
+    pub symbol: Option<String>,
+    pub side: Option<Side>,
+    pub order_type: Option<OrderType>,
+    pub time_in_force: Option<TimeInForce>,
+    #[serde(default, with = "crate::types::decimal::number_option")]
+    pub order_qty: Option<Decimal>,
+    #[serde(default, with = "crate::types::decimal::number_option")]
+    pub limit_price: Option<Decimal>,
+    /// Quantity and price of this fill.
+    #[serde(default, with = "crate::types::decimal::number_option")]
+    pub last_qty: Option<Decimal>,
+    #[serde(default, with = "crate::types::decimal::number_option")]
+    pub last_price: Option<Decimal>,
+    #[serde(default, with = "crate::types::decimal::number_option")]
+    pub cost: Option<Decimal>,
+    /// Totals over every fill of the order so far.
+    #[serde(default, with = "crate::types::decimal::number_option")]
+    pub cum_qty: Option<Decimal>,
+    #[serde(default, with = "crate::types::decimal::number_option")]
+    pub cum_cost: Option<Decimal>,
+    #[serde(default, with = "crate::types::decimal::number_option")]
+    pub avg_price: Option<Decimal>,
+    pub liquidity_ind: Option<Liquidity>,
+    pub fees: Option<Vec<Fee>>,
+    #[serde(default, with = "crate::types::decimal::number_option")]
+    pub fee_usd_equiv: Option<Decimal>,
+    pub amended: Option<bool>,
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecType {
+    PendingNew,
+    New,
+    Trade,
+    Filled,
+    IcebergRefill,
+    Canceled,
+    Expired,
+    Amended,
+    Restated,
+    Status,
+    /// A value added by Kraken after this SDK was released.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    PendingNew,
+    New,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    Expired,
+    #[serde(other)]
+    Unknown,
+}
+
+impl OrderStatus {
+    /// Whether the order has left the book for good.
+    pub fn is_closed(self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Filled | OrderStatus::Canceled | OrderStatus::Expired
+        )
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Liquidity {
+    #[serde(rename = "m")]
+    Maker,
+    #[serde(rename = "t")]
+    Taker,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Fee {
+    pub asset: String,
+    #[serde(with = "crate::types::decimal::number")]
+    pub qty: Decimal,
+}
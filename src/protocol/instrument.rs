@@ -0,0 +1,61 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use super::event::MessageType;
+
+#[derive(Deserialize, Debug)]
+pub struct InstrumentWrapper {
+    pub channel: String,
+    #[serde(rename = "type")]
+    pub event_type: MessageType,
+    pub data: InstrumentData,
+}
+
+/// A snapshot lists every asset and pair; an update only the changed ones.
+#[derive(Deserialize, Debug, Default)]
+pub struct InstrumentData {
+    #[serde(default)]
+    pub assets: Vec<AssetInfo>,
+    #[serde(default)]
+    pub pairs: Vec<PairInfo>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct AssetInfo {
+    pub id: String,
+    /// `enabled`, `deposit_only`, `withdrawal_only`, `funding_temporarily_disabled`, ...
+    pub status: String,
+    pub precision: u32,
+    pub precision_display: u32,
+    #[serde(default)]
+    pub borrowable: bool,
+    #[serde(default, with = "crate::types::decimal::number_option")]
+    pub collateral_value: Option<Decimal>,
+    #[serde(default, with = "crate::types::decimal::number_option")]
+    pub margin_rate: Option<Decimal>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct PairInfo {
+    pub symbol: String,
+    pub base: String,
+    pub quote: String,
+    /// `online`, `cancel_only`, `post_only`, `limit_only`, `reduce_only`,
+    /// `maintenance`, `delisted`, ...
+    pub status: String,
+    pub price_precision: u32,
+    #[serde(with = "crate::types::decimal::number")]
+    pub price_increment: Decimal,
+    pub qty_precision: u32,
+    #[serde(with = "crate::types::decimal::number")]
+    pub qty_increment: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub qty_min: Decimal,
+    pub cost_precision: u32,
+    #[serde(with = "crate::types::decimal::number")]
+    pub cost_min: Decimal,
+    #[serde(default)]
+    pub marginable: bool,
+    #[serde(default)]
+    pub has_index: bool,
+}
@@ -0,0 +1,44 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use super::event::MessageType;
+
+#[derive(Deserialize, Debug)]
+pub struct Level3Wrapper {
+    pub channel: String,
+    #[serde(rename = "type")]
+    pub event_type: MessageType,
+    pub data: Vec<Level3Event>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Level3Event {
+    pub symbol: String,
+    pub bids: Vec<Level3Order>,
+    pub asks: Vec<Level3Order>,
+    pub checksum: u32,
+    pub timestamp: Option<String>,
+}
+
+/// One resting order. Snapshots list orders without an `event`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Level3Order {
+    #[serde(default)]
+    pub event: Option<Level3Action>,
+    pub order_id: String,
+    #[serde(with = "crate::types::decimal::number")]
+    pub limit_price: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub order_qty: Decimal,
+    pub timestamp: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Level3Action {
+    Add,
+    /// The order's quantity changed, e.g. after a partial fill. It keeps
+    /// its place in the queue.
+    Modify,
+    Delete,
+}
@@ -1,5 +1,12 @@
+pub mod balances;
+#[cfg(feature = "zero-copy")]
+pub mod borrowed;
 pub mod event;
-pub mod order;
+pub mod executions;
+pub mod instrument;
+pub mod level3;
+pub mod ohlc;
 pub mod ping;
+pub mod response;
 pub mod subscribe;
 pub mod trades;
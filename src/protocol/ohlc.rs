@@ -0,0 +1,36 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use super::event::MessageType;
+
+#[derive(Deserialize, Debug)]
+pub struct OhlcWrapper {
+    pub channel: String,
+    #[serde(rename = "type")]
+    pub event_type: MessageType,
+    pub data: Vec<OhlcEvent>,
+}
+
+/// One candle. Updates keep arriving for the candle in progress; the last
+/// one before `interval_begin` moves on is final.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct OhlcEvent {
+    pub symbol: String,
+    #[serde(with = "crate::types::decimal::number")]
+    pub open: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub high: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub low: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub close: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub vwap: Decimal,
+    pub trades: u64,
+    #[serde(with = "crate::types::decimal::number")]
+    pub volume: Decimal,
+    pub interval_begin: String,
+    /// Candle length in minutes.
+    pub interval: u32,
+    pub timestamp: Option<String>,
+}
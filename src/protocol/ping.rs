@@ -3,11 +3,15 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize)]
 pub struct PingMessage {
     pub method: &'static str,
+    pub req_id: u64,
 }
 
 impl PingMessage {
-    pub fn new() -> Self {
-        Self { method: "ping" }
+    pub fn new(req_id: u64) -> Self {
+        Self {
+            method: "ping",
+            req_id,
+        }
     }
 }
 
@@ -0,0 +1,37 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::{Error, Result};
+
+/// Reply to any `method` request, with the result left untyped until the
+/// caller knows what it asked for.
+#[derive(Deserialize, Debug, Clone)]
+pub struct MethodResponse {
+    pub method: String,
+    pub req_id: Option<u64>,
+    pub success: bool,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    /// The symbol a per-symbol rejection, such as a subscribe error, is for.
+    pub symbol: Option<String>,
+    pub time_in: Option<String>,
+    pub time_out: Option<String>,
+}
+
+impl MethodResponse {
+    pub fn into_result<T: DeserializeOwned>(self) -> Result<T> {
+        if !self.success {
+            return Err(Error::Api {
+                method: self.method,
+                message: self.error.unwrap_or_default(),
+            });
+        }
+        let result = self.result.unwrap_or(serde_json::Value::Null);
+        serde_json::from_value(result).map_err(|e| Error::Json(Box::new(e)))
+    }
+
+    /// Every response's typed result, or the first error among them.
+    pub fn into_results<T: DeserializeOwned>(responses: Vec<Self>) -> Result<Vec<T>> {
+        responses.into_iter().map(Self::into_result).collect()
+    }
+}
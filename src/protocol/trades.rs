@@ -1,10 +1,13 @@
+use rust_decimal::Decimal;
 use serde::Deserialize;
 
+use super::event::MessageType;
+
 #[derive(Deserialize, Debug)]
 pub struct TradeWrapper {
     pub channel: String,
     #[serde(rename = "type")]
-    pub event_type: String,
+    pub event_type: MessageType,
     pub data: Vec<TradeEvent>,
 }
 
@@ -12,8 +15,11 @@ pub struct TradeWrapper {
 pub struct TradeEvent {
     pub symbol: String,
     pub side: String,
-    pub qty: String,
-    pub price: String,
+    #[serde(with = "crate::types::decimal::number")]
+    pub qty: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub price: Decimal,
+    pub ord_type: String,
     pub trade_id: u64,
     pub timestamp: String,
 }
@@ -22,14 +28,23 @@ pub struct TradeEvent {
 pub struct OrderbookWrapper {
     pub channel: String,
     #[serde(rename = "type")]
-    pub event_type: String,
+    pub event_type: MessageType,
     pub data: Vec<OrderbookEvent>,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct OrderbookEvent {
     pub symbol: String,
-    pub bids: Vec<[String; 3]>,
-    pub asks: Vec<[String; 3]>,
-    pub checksum: Option<u32>,
+    pub bids: Vec<PriceLevel>,
+    pub asks: Vec<PriceLevel>,
+    pub checksum: u32,
+    pub timestamp: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceLevel {
+    #[serde(with = "crate::types::decimal::number")]
+    pub price: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub qty: Decimal,
 }
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use crate::types::{SubscribeInput, SubscribeParams, Subscription};
+
+#[derive(Debug, Default)]
+struct Inner {
+    active: BTreeMap<Subscription, SubscribeParams>,
+    live: bool,
+}
+
+/// Active subscriptions keyed per channel and symbol, shared by every clone
+/// of a `Client` and replayed by the session whenever it (re)connects.
+///
+/// While the session is not live, changes are only recorded; the replay
+/// snapshot and the switch to live happen under one lock so nothing is
+/// sent twice or lost in between.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SubscriptionRegistry {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl SubscriptionRegistry {
+    /// Records `input` and returns the part that still needs to be sent, or
+    /// `None` when every symbol is already subscribed with the same params or
+    /// the session is not live (the replay will send it).
+    pub(crate) fn subscribe(&self, mut input: SubscribeInput) -> Option<SubscribeInput> {
+        let mut inner = self.inner.lock().unwrap();
+        let active = &mut inner.active;
+        let fresh: Vec<Option<String>> = Self::split(&input.params)
+            .into_iter()
+            .filter_map(|(key, params)| {
+                if active.get(&key) == Some(&params) {
+                    return None;
+                }
+                active.insert(key.clone(), params);
+                Some(key.symbol)
+            })
+            .collect();
+
+        if fresh.is_empty() || !inner.live {
+            return None;
+        }
+        input.params.symbol = fresh.into_iter().flatten().collect();
+        Some(input)
+    }
+
+    /// Forgets `input` and returns the part that was actually active, or
+    /// `None` when nothing in it was subscribed or the session is not live.
+    /// Options such as `depth` are taken from the original subscription,
+    /// since Kraken only matches an unsubscribe that repeats them.
+    pub(crate) fn unsubscribe(&self, mut input: SubscribeInput) -> Option<SubscribeInput> {
+        let mut inner = self.inner.lock().unwrap();
+        let active = &mut inner.active;
+        let mut removed = Self::split(&input.params)
+            .into_iter()
+            .filter_map(|(key, _)| active.remove(&key).map(|params| (key.symbol, params)))
+            .peekable();
+
+        let original = Self::without_symbol(&removed.peek()?.1);
+        let symbols = removed.filter_map(|(symbol, _)| symbol).collect();
+        if !inner.live {
+            return None;
+        }
+        input.params = SubscribeParams {
+            symbol: symbols,
+            ..original
+        };
+        Some(input)
+    }
+
+    pub(crate) fn params(&self, key: &Subscription) -> Option<SubscribeParams> {
+        self.inner.lock().unwrap().active.get(key).cloned()
+    }
+
+    pub(crate) fn list(&self) -> Vec<Subscription> {
+        self.inner.lock().unwrap().active.keys().cloned().collect()
+    }
+
+    /// Marks the session live and returns subscribe messages restoring every
+    /// active subscription, batching symbols that share channel and params.
+    pub(crate) fn go_live(&self) -> Vec<SubscribeInput> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.live = true;
+        let mut groups: Vec<SubscribeParams> = Vec::new();
+
+        for params in inner.active.values() {
+            let shared = Self::without_symbol(params);
+            match groups
+                .iter_mut()
+                .find(|group| Self::without_symbol(group) == shared)
+            {
+                Some(group) => group.symbol.extend(params.symbol.iter().cloned()),
+                None => groups.push(params.clone()),
+            }
+        }
+
+        groups
+            .into_iter()
+            .map(|params| SubscribeInput {
+                method: "subscribe".to_string(),
+                params,
+                req_id: None,
+            })
+            .collect()
+    }
+
+    pub(crate) fn go_offline(&self) {
+        self.inner.lock().unwrap().live = false;
+    }
+
+    fn without_symbol(params: &SubscribeParams) -> SubscribeParams {
+        SubscribeParams {
+            symbol: Vec::new(),
+            ..params.clone()
+        }
+    }
+
+    fn split(params: &SubscribeParams) -> Vec<(Subscription, SubscribeParams)> {
+        if params.symbol.is_empty() {
+            let key = Subscription {
+                channel: params.channel,
+                symbol: None,
+            };
+            return vec![(key, params.clone())];
+        }
+
+        params
+            .symbol
+            .iter()
+            .map(|symbol| {
+                let key = Subscription {
+                    channel: params.channel,
+                    symbol: Some(symbol.clone()),
+                };
+                let single = SubscribeParams {
+                    symbol: vec![symbol.clone()],
+                    ..params.clone()
+                };
+                (key, single)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Channel;
+
+    fn symbols(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_subscribe_deduplicates() {
+        let registry = SubscriptionRegistry::default();
+        registry.go_live();
+        let first = registry.subscribe(SubscribeInput::ticker(symbols(&["BTC/USD"])));
+        assert!(first.is_some());
+
+        let repeat = registry.subscribe(SubscribeInput::ticker(symbols(&["BTC/USD", "ETH/USD"])));
+        assert_eq!(repeat.unwrap().params.symbol, symbols(&["ETH/USD"]));
+
+        assert!(registry
+            .subscribe(SubscribeInput::ticker(symbols(&["ETH/USD"])))
+            .is_none());
+        assert_eq!(registry.list().len(), 2);
+    }
+
+    #[test]
+    fn test_unsubscribe_only_sends_active_symbols() {
+        let registry = SubscriptionRegistry::default();
+        registry.go_live();
+        registry.subscribe(SubscribeInput::ticker(symbols(&["BTC/USD", "ETH/USD"])));
+
+        let input = SubscribeInput::unsubscribe(Channel::Ticker, symbols(&["ETH/USD", "XRP/USD"]));
+        let sent = registry.unsubscribe(input).unwrap();
+        assert_eq!(sent.params.symbol, symbols(&["ETH/USD"]));
+        assert_eq!(
+            registry.list(),
+            vec![Subscription {
+                channel: Channel::Ticker,
+                symbol: Some("BTC/USD".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_offline_changes_are_replayed_in_batches() {
+        let registry = SubscriptionRegistry::default();
+        assert!(registry
+            .subscribe(SubscribeInput::ticker(symbols(&["BTC/USD"])))
+            .is_none());
+        assert!(registry
+            .subscribe(SubscribeInput::ticker(symbols(&["ETH/USD"])))
+            .is_none());
+
+        let replay = registry.go_live();
+        assert_eq!(replay.len(), 1);
+        assert_eq!(replay[0].params.symbol, symbols(&["BTC/USD", "ETH/USD"]));
+    }
+
+    #[test]
+    fn test_unsubscribe_repeats_subscription_options() {
+        let registry = SubscriptionRegistry::default();
+        registry.go_live();
+        registry.subscribe(SubscribeInput::book(symbols(&["BTC/USD"]), Some(25)));
+
+        let input = SubscribeInput::unsubscribe(Channel::Book, symbols(&["BTC/USD"]));
+        let sent = registry.unsubscribe(input).unwrap();
+        assert_eq!(sent.params.depth, Some(25));
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+use crate::protocol::response::MethodResponse;
+use crate::types::{
+    AddOrderRequest, AmendOrderRequest, BatchCancelRequest, BatchOrderRequest,
+    CancelAllOrdersAfterRequest, CancelAllRequest, CancelOrderRequest, EditOrderRequest,
+    SubscribeInput,
+};
+
+/// A request that carries a WebSocket token, which the client fills in
+/// from its `TokenProvider`.
+pub(crate) trait PrivateRequest: serde::Serialize {
+    fn token_mut(&mut self) -> &mut Option<String>;
+    fn req_id_mut(&mut self) -> &mut Option<u64>;
+}
+
+impl PrivateRequest for AddOrderRequest {
+    fn token_mut(&mut self) -> &mut Option<String> {
+        &mut self.params.token
+    }
+
+    fn req_id_mut(&mut self) -> &mut Option<u64> {
+        &mut self.req_id
+    }
+}
+
+impl PrivateRequest for AmendOrderRequest {
+    fn token_mut(&mut self) -> &mut Option<String> {
+        &mut self.params.token
+    }
+
+    fn req_id_mut(&mut self) -> &mut Option<u64> {
+        &mut self.req_id
+    }
+}
+
+impl PrivateRequest for EditOrderRequest {
+    fn token_mut(&mut self) -> &mut Option<String> {
+        &mut self.params.token
+    }
+
+    fn req_id_mut(&mut self) -> &mut Option<u64> {
+        &mut self.req_id
+    }
+}
+
+impl PrivateRequest for CancelOrderRequest {
+    fn token_mut(&mut self) -> &mut Option<String> {
+        &mut self.params.token
+    }
+
+    fn req_id_mut(&mut self) -> &mut Option<u64> {
+        &mut self.req_id
+    }
+}
+
+impl PrivateRequest for CancelAllRequest {
+    fn token_mut(&mut self) -> &mut Option<String> {
+        &mut self.params.token
+    }
+
+    fn req_id_mut(&mut self) -> &mut Option<u64> {
+        &mut self.req_id
+    }
+}
+
+impl PrivateRequest for CancelAllOrdersAfterRequest {
+    fn token_mut(&mut self) -> &mut Option<String> {
+        &mut self.params.token
+    }
+
+    fn req_id_mut(&mut self) -> &mut Option<u64> {
+        &mut self.req_id
+    }
+}
+
+impl PrivateRequest for BatchCancelRequest {
+    fn token_mut(&mut self) -> &mut Option<String> {
+        &mut self.params.token
+    }
+
+    fn req_id_mut(&mut self) -> &mut Option<u64> {
+        &mut self.req_id
+    }
+}
+
+impl PrivateRequest for BatchOrderRequest {
+    fn token_mut(&mut self) -> &mut Option<String> {
+        &mut self.params.token
+    }
+
+    fn req_id_mut(&mut self) -> &mut Option<u64> {
+        &mut self.req_id
+    }
+}
+
+impl PrivateRequest for SubscribeInput {
+    fn token_mut(&mut self) -> &mut Option<String> {
+        &mut self.params.token
+    }
+
+    fn req_id_mut(&mut self) -> &mut Option<u64> {
+        &mut self.req_id
+    }
+}
+
+/// Errors Kraken returns for a token that expired or is not (or no longer)
+/// valid.
+const TOKEN_ERRORS: [&str; 3] = [
+    "EAPI:Invalid session",
+    "ESession:Invalid session",
+    "EAPI:Invalid token",
+];
+
+/// Whether Kraken rejected a request because of its token, so a fresh one
+/// may succeed. Other errors merely mentioning a token are not retried.
+pub(crate) fn is_token_error(message: &str) -> bool {
+    let message = message.trim();
+    TOKEN_ERRORS
+        .iter()
+        .any(|error| message.eq_ignore_ascii_case(error))
+}
+
+struct Pending {
+    expected: usize,
+    received: Vec<MethodResponse>,
+    tx: oneshot::Sender<Vec<MethodResponse>>,
+}
+
+/// Hands out `req_id`s and routes the matching responses back to whoever
+/// is awaiting them. Shared between every `Client` clone and the session.
+#[derive(Clone, Default)]
+pub(crate) struct RequestTracker {
+    next_id: Arc<AtomicU64>,
+    pending: Arc<Mutex<HashMap<u64, Pending>>>,
+}
+
+impl std::fmt::Debug for RequestTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequestTracker")
+            .field("next_id", &self.next_id)
+            .field("pending", &self.pending.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl RequestTracker {
+    pub(crate) fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Waits for `expected` responses carrying `req_id`; Kraken answers a
+    /// multi-symbol subscribe with one response per symbol, for example.
+    pub(crate) fn register(
+        &self,
+        req_id: u64,
+        expected: usize,
+    ) -> oneshot::Receiver<Vec<MethodResponse>> {
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(
+            req_id,
+            Pending {
+                expected: expected.max(1),
+                received: Vec::new(),
+                tx,
+            },
+        );
+        rx
+    }
+
+    pub(crate) fn resolve(&self, response: MethodResponse) {
+        let Some(req_id) = response.req_id else {
+            return;
+        };
+        let mut pending = self.pending.lock().unwrap();
+        let Some(entry) = pending.get_mut(&req_id) else {
+            return;
+        };
+
+        // An error for the whole request settles it immediately; there is
+        // nothing to wait for. One naming a symbol counts like an ack, since
+        // the other symbols are still answered.
+        let failed = !response.success && response.symbol.is_none();
+        entry.received.push(response);
+        if failed || entry.received.len() >= entry.expected {
+            if let Some(entry) = pending.remove(&req_id) {
+                let _ = entry.tx.send(entry.received);
+            }
+        }
+    }
+
+    pub(crate) fn cancel(&self, req_id: u64) {
+        self.pending.lock().unwrap().remove(&req_id);
+    }
+
+    /// Drops every outstanding request, failing their waiters with
+    /// `Error::ConnectionClosed`.
+    pub(crate) fn fail_all(&self) {
+        self.pending.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_token_errors_are_retried() {
+        assert!(is_token_error("EAPI:Invalid session"));
+        assert!(is_token_error("ESession:Invalid session"));
+        assert!(is_token_error("EAPI:Invalid token"));
+
+        assert!(!is_token_error("EOrder:Invalid price, token pair halted"));
+        assert!(!is_token_error("EGeneral:Invalid arguments:token"));
+        assert!(!is_token_error("EOrder:Insufficient funds"));
+    }
+}
@@ -0,0 +1,49 @@
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    Disconnected { reason: String },
+    Reconnecting { attempt: u32 },
+    GaveUp,
+}
+
+impl ConnectionState {
+    pub fn is_connected(&self) -> bool {
+        matches!(self, ConnectionState::Connected)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct StateTracker {
+    current: Arc<Mutex<ConnectionState>>,
+    tx: broadcast::Sender<ConnectionState>,
+}
+
+impl StateTracker {
+    pub(crate) fn new() -> Self {
+        let (tx, _) = broadcast::channel(32);
+        Self {
+            current: Arc::new(Mutex::new(ConnectionState::Disconnected {
+                reason: "not connected".to_string(),
+            })),
+            tx,
+        }
+    }
+
+    pub(crate) fn set(&self, state: ConnectionState) {
+        *self.current.lock().unwrap() = state.clone();
+        // No subscribers is not an error: state is still readable via `current`.
+        let _ = self.tx.send(state);
+    }
+
+    pub(crate) fn current(&self) -> ConnectionState {
+        self.current.lock().unwrap().clone()
+    }
+
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<ConnectionState> {
+        self.tx.subscribe()
+    }
+}
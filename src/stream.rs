@@ -1,4 +1,8 @@
 use crate::metrics::Metrics;
+#[cfg(feature = "zero-copy")]
+use crate::parser::ZeroCopyParser;
+#[cfg(feature = "zero-copy")]
+use crate::protocol::borrowed::BorrowedEvent;
 use crate::protocol::event::KrakenEvent;
 use std::pin::Pin;
 use std::task::{Context, Poll};
@@ -11,11 +15,8 @@ pub struct KrakenStream {
 }
 
 impl KrakenStream {
-    pub(crate) fn new(rx: mpsc::Receiver<KrakenEvent>) -> Self {
-        Self {
-            rx,
-            metrics: Metrics::default(),
-        }
+    pub(crate) fn new(rx: mpsc::Receiver<KrakenEvent>, metrics: Metrics) -> Self {
+        Self { rx, metrics }
     }
 
     pub fn metrics(&self) -> &Metrics {
@@ -42,3 +43,36 @@ impl Stream for KrakenStream {
         }
     }
 }
+
+/// Frames from `Client::raw_stream`, parsed in place on demand. Each event
+/// borrows the stream until the next call, so nothing is copied out of the
+/// frame and the parser's buffers are reused.
+#[cfg(feature = "zero-copy")]
+pub struct RawStream {
+    rx: mpsc::Receiver<Vec<u8>>,
+    frame: Vec<u8>,
+    parser: ZeroCopyParser,
+    metrics: Metrics,
+}
+
+#[cfg(feature = "zero-copy")]
+impl RawStream {
+    pub(crate) fn new(rx: mpsc::Receiver<Vec<u8>>, metrics: Metrics) -> Self {
+        Self {
+            rx,
+            frame: Vec::new(),
+            parser: ZeroCopyParser::default(),
+            metrics,
+        }
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    pub async fn next(&mut self) -> Option<crate::Result<BorrowedEvent<'_>>> {
+        self.frame = self.rx.recv().await?;
+        self.metrics.record_message();
+        Some(self.parser.parse(&mut self.frame))
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::{Auth, Error, Result};
+
+pub const DEFAULT_REST_URL: &str = "https://api.kraken.com";
+const TOKEN_PATH: &str = "/0/private/GetWebSocketsToken";
+
+/// Fetches the WebSocket token for private channels and methods from
+/// Kraken's REST `GetWebSocketsToken` endpoint, and keeps it until shortly
+/// before it expires.
+///
+/// Clones share the cached token.
+#[derive(Clone, Debug)]
+pub struct TokenProvider {
+    auth: Auth,
+    base_url: String,
+    refresh_before: Duration,
+    http: reqwest::Client,
+    cached: Arc<Mutex<Option<CachedToken>>>,
+}
+
+#[derive(Debug)]
+struct CachedToken {
+    token: String,
+    expires_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct RestResponse<T> {
+    #[serde(default)]
+    error: Vec<String>,
+    result: Option<T>,
+}
+
+#[derive(Deserialize)]
+struct WebSocketsToken {
+    token: String,
+    /// Seconds the token may go unused before Kraken rejects it.
+    expires: u64,
+}
+
+impl TokenProvider {
+    pub fn new(auth: Auth) -> Self {
+        Self {
+            auth,
+            base_url: DEFAULT_REST_URL.to_string(),
+            refresh_before: Duration::from_secs(60),
+            http: reqwest::Client::new(),
+            cached: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// REST endpoint to fetch tokens from, `https://api.kraken.com` by default.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// How long before expiry a cached token is replaced (60s by default).
+    pub fn refresh_before(mut self, refresh_before: Duration) -> Self {
+        self.refresh_before = refresh_before;
+        self
+    }
+
+    pub fn auth(&self) -> &Auth {
+        &self.auth
+    }
+
+    /// The cached token, or a new one if there is none or it is about to
+    /// expire. Concurrent callers wait for a single fetch.
+    pub async fn token(&self) -> Result<String> {
+        let mut cached = self.cached.lock().await;
+        if let Some(current) = cached.as_ref() {
+            if Instant::now() + self.refresh_before < current.expires_at {
+                return Ok(current.token.clone());
+            }
+        }
+        self.fetch_into(&mut cached).await
+    }
+
+    /// Fetches a new token regardless of the cached one, e.g. after Kraken
+    /// rejected it.
+    pub async fn refresh(&self) -> Result<String> {
+        let mut cached = self.cached.lock().await;
+        self.fetch_into(&mut cached).await
+    }
+
+    /// Drops the cached token so the next `token()` fetches a new one.
+    pub async fn invalidate(&self) {
+        *self.cached.lock().await = None;
+    }
+
+    async fn fetch_into(&self, cached: &mut Option<CachedToken>) -> Result<String> {
+        let fetched = self.fetch().await?;
+        let token = fetched.token.clone();
+        *cached = Some(CachedToken {
+            token: fetched.token,
+            expires_at: Instant::now() + Duration::from_secs(fetched.expires),
+        });
+        Ok(token)
+    }
+
+    async fn fetch(&self) -> Result<WebSocketsToken> {
+        let nonce = self.auth.nonce();
+        let body = format!("nonce={nonce}");
+        let signature = self.auth.sign(TOKEN_PATH, nonce, &body)?;
+
+        let response: RestResponse<WebSocketsToken> = self
+            .http
+            .post(format!("{}{}", self.base_url, TOKEN_PATH))
+            .header("API-Key", self.auth.api_key())
+            .header("API-Sign", signature)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| Error::Http(Box::new(e)))?
+            .json()
+            .await
+            .map_err(|e| Error::Http(Box::new(e)))?;
+
+        match response.result {
+            Some(token) if response.error.is_empty() => Ok(token),
+            _ => Err(Error::Api {
+                method: "GetWebSocketsToken".to_string(),
+                message: response.error.join(", "),
+            }),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Serde helpers for `Decimal` prices and quantities. Kraken sends and
+//! expects plain JSON numbers. serde_json's `arbitrary_precision` hands
+//! over the number token itself, so every digit the exchange sent is kept,
+//! trailing zeros included, and the same digits are written back.
+//!
+//! simd-json only reads numbers as `f64`; see `ZeroCopyParser` for what
+//! that means for the `zero-copy` types.
+
+pub(crate) mod number {
+    pub use rust_decimal::serde::arbitrary_precision::{deserialize, serialize};
+}
+
+pub(crate) mod number_option {
+    pub use rust_decimal::serde::arbitrary_precision_option::{deserialize, serialize};
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use serde::{Deserialize, Serialize};
+    use std::str::FromStr;
+
+    #[derive(Serialize, Deserialize)]
+    struct Level {
+        #[serde(with = "super::number")]
+        price: Decimal,
+        #[serde(with = "super::number_option", default)]
+        qty: Option<Decimal>,
+    }
+
+    #[test]
+    fn test_numbers_round_trip_exactly() {
+        let level: Level = serde_json::from_str(r#"{"price":0.1,"qty":0.00000001}"#).unwrap();
+        assert_eq!(level.price, Decimal::from_str("0.1").unwrap());
+        assert_eq!(level.qty, Some(Decimal::from_str("0.00000001").unwrap()));
+
+        let level = Level {
+            price: Decimal::from_str("42000.10").unwrap(),
+            qty: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&level).unwrap(),
+            r#"{"price":42000.10,"qty":null}"#
+        );
+    }
+
+    #[test]
+    fn test_numbers_keep_the_exchange_digits() {
+        // More significant digits than an f64 holds, and trailing zeros.
+        let text = r#"{"price":12345.678901234567890123,"qty":0.10000}"#;
+        let level: Level = serde_json::from_str(text).unwrap();
+        assert_eq!(level.price.to_string(), "12345.678901234567890123");
+        assert_eq!(level.qty.unwrap().to_string(), "0.10000");
+        assert_eq!(serde_json::to_string(&level).unwrap(), text);
+
+        // Also when the message was first read into a `Value`.
+        let value: serde_json::Value = serde_json::from_str(text).unwrap();
+        let level: Level = serde_json::from_value(value).unwrap();
+        assert_eq!(level.qty.unwrap().to_string(), "0.10000");
+    }
+}
@@ -1,3 +1,4 @@
+pub(crate) mod decimal;
 pub mod order;
 pub mod subscription;
 pub mod ticker;
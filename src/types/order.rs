@@ -1,20 +1,179 @@
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use crate::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+    /// A value added by Kraken after this SDK was released; only ever
+    /// received, never sent.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OrderType {
+    Limit,
+    Market,
+    Iceberg,
+    StopLoss,
+    StopLossLimit,
+    TakeProfit,
+    TakeProfitLimit,
+    TrailingStop,
+    TrailingStopLimit,
+    SettlePosition,
+    /// A value added by Kraken after this SDK was released; only ever
+    /// received, never sent.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeInForce {
+    /// Good till canceled (Kraken's default).
+    Gtc,
+    /// Good till `expire_time`.
+    Gtd,
+    /// Immediate or cancel.
+    Ioc,
+    /// A value added by Kraken after this SDK was released; only ever
+    /// received, never sent.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Self trade prevention: which side of a self-matching trade is canceled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StpType {
+    CancelNewest,
+    CancelOldest,
+    CancelBoth,
+}
+
+/// Currency the fee is taken in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeePreference {
+    Base,
+    Quote,
+}
+
+/// Price a trigger watches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TriggerReference {
+    Index,
+    Last,
+}
+
+/// How a trigger or limit price is given: as is, as a percentage offset or
+/// as a quote currency offset from the reference price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceType {
+    Static,
+    Pct,
+    Quote,
+}
+
+/// Trigger for stop-loss, take-profit and trailing-stop orders.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Triggers {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<TriggerReference>,
+    #[serde(with = "crate::types::decimal::number")]
+    pub price: Decimal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_type: Option<PriceType>,
+}
+
+impl Triggers {
+    /// A fixed trigger price against the last trade.
+    pub fn last(price: Decimal) -> Self {
+        Self {
+            reference: Some(TriggerReference::Last),
+            price,
+            price_type: Some(PriceType::Static),
+        }
+    }
+}
+
+/// An `add_order` request, serialized as Kraken's v2 schema.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddOrderRequest {
     pub method: String,
     pub params: AddOrderParams,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub req_id: Option<u64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AddOrderParams {
-    pub ordertype: String,
-    pub pair: String,
-    pub side: String,
-    pub volume: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub price: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_type: OrderType,
+    pub side: Side,
+    /// Left out of `batch_add` legs, which take the batch's symbol.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub symbol: String,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::types::decimal::number_option"
+    )]
+    pub order_qty: Option<Decimal>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::types::decimal::number_option"
+    )]
+    pub limit_price: Option<Decimal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_price_type: Option<PriceType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub triggers: Option<Triggers>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_in_force: Option<TimeInForce>,
+    /// RFC 3339 time a `Gtd` order expires at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expire_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_only: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reduce_only: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub margin: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cl_ord_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_userref: Option<i64>,
+    /// Visible quantity of an iceberg order.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::types::decimal::number_option"
+    )]
+    pub display_qty: Option<Decimal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stp_type: Option<StpType>,
+    /// Quote currency amount to spend, instead of `order_qty`, on market buys.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::types::decimal::number_option"
+    )]
+    pub cash_order_qty: Option<Decimal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_preference: Option<FeePreference>,
+    /// Validate the order without placing it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validate: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub token: Option<String>,
 }
 
@@ -22,11 +181,45 @@ pub struct AddOrderParams {
 pub struct CancelOrderRequest {
     pub method: String,
     pub params: CancelOrderParams,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub req_id: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CancelOrderParams {
-    pub txid: Vec<String>,
+    pub order_id: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelAllRequest {
+    pub method: String,
+    pub params: TokenParams,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub req_id: Option<u64>,
+}
+
+/// Params of requests that take nothing but the token.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// Kraken's dead man's switch: cancels every open order once `timeout`
+/// seconds pass without another such request. A timeout of 0 disarms it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelAllOrdersAfterRequest {
+    pub method: String,
+    pub params: CancelAllOrdersAfterParams,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub req_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelAllOrdersAfterParams {
+    pub timeout: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub token: Option<String>,
 }
@@ -35,98 +228,804 @@ pub struct CancelOrderParams {
 pub struct BatchOrderRequest {
     pub method: String,
     pub params: BatchOrderParams,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub req_id: Option<u64>,
 }
 
+/// `batch_add` legs, which must all trade the batch's `symbol`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchOrderParams {
+    pub symbol: String,
+    #[serde(serialize_with = "serialize_legs")]
     pub orders: Vec<AddOrderParams>,
+    /// RFC 3339 time after which the engine should reject the batch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validate: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+fn serialize_legs<S: serde::Serializer>(
+    orders: &[AddOrderParams],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(orders.iter().map(|order| AddOrderParams {
+        symbol: String::new(),
+        ..order.clone()
+    }))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchCancelRequest {
+    pub method: String,
+    pub params: BatchCancelParams,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub req_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchCancelParams {
+    /// Order ids and `order_userref`s, which Kraken accepts side by side.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub orders: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cl_ord_id: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// Changes a resting order in place. The order keeps its id and, unless its
+/// price changes or its quantity grows, its place in the queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmendOrderRequest {
+    pub method: String,
+    pub params: AmendOrderParams,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub req_id: Option<u64>,
+}
+
+/// Identifies the order by `order_id` or `cl_ord_id`; every other field is
+/// left unchanged when `None`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AmendOrderParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cl_ord_id: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::types::decimal::number_option"
+    )]
+    pub order_qty: Option<Decimal>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::types::decimal::number_option"
+    )]
+    pub display_qty: Option<Decimal>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::types::decimal::number_option"
+    )]
+    pub limit_price: Option<Decimal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_price_type: Option<PriceType>,
+    /// Reject the amend if the new price would take liquidity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_only: Option<bool>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::types::decimal::number_option"
+    )]
+    pub trigger_price: Option<Decimal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigger_price_type: Option<PriceType>,
+    /// RFC 3339 time after which the engine should reject the amend.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// Replaces a resting order with a new one under a new order id; the order
+/// loses its queue priority. Prefer `AmendOrderRequest` where it suffices.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditOrderRequest {
+    pub method: String,
+    pub params: EditOrderParams,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub req_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EditOrderParams {
+    pub order_id: String,
+    pub symbol: String,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::types::decimal::number_option"
+    )]
+    pub order_qty: Option<Decimal>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::types::decimal::number_option"
+    )]
+    pub display_qty: Option<Decimal>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::types::decimal::number_option"
+    )]
+    pub limit_price: Option<Decimal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub triggers: Option<Triggers>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_only: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reduce_only: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_userref: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_preference: Option<FeePreference>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validate: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub token: Option<String>,
 }
 
 impl AddOrderRequest {
-    pub fn market_buy(pair: &str, volume: &str) -> Self {
+    /// An order with only the required fields set; `order_qty` may be
+    /// `None` for market buys given in `cash_order_qty`.
+    pub fn new(
+        order_type: OrderType,
+        side: Side,
+        symbol: impl Into<String>,
+        order_qty: impl Into<Option<Decimal>>,
+    ) -> Self {
         Self {
             method: "add_order".to_string(),
             params: AddOrderParams {
-                ordertype: "market".to_string(),
-                pair: pair.to_string(),
-                side: "buy".to_string(),
-                volume: volume.to_string(),
-                price: None,
+                order_type,
+                side,
+                symbol: symbol.into(),
+                order_qty: order_qty.into(),
+                limit_price: None,
+                limit_price_type: None,
+                triggers: None,
+                time_in_force: None,
+                expire_time: None,
+                post_only: None,
+                reduce_only: None,
+                margin: None,
+                cl_ord_id: None,
+                order_userref: None,
+                display_qty: None,
+                stp_type: None,
+                cash_order_qty: None,
+                fee_preference: None,
+                validate: None,
                 token: None,
             },
+            req_id: None,
         }
     }
 
-    pub fn limit_buy(pair: &str, volume: &str, price: &str) -> Self {
+    pub fn market_buy(symbol: &str, qty: Decimal) -> Self {
+        Self::new(OrderType::Market, Side::Buy, symbol, qty)
+    }
+
+    pub fn limit_buy(symbol: &str, qty: Decimal, price: Decimal) -> Self {
+        Self::new(OrderType::Limit, Side::Buy, symbol, qty).limit_price(price)
+    }
+
+    pub fn market_sell(symbol: &str, qty: Decimal) -> Self {
+        Self::new(OrderType::Market, Side::Sell, symbol, qty)
+    }
+
+    pub fn limit_sell(symbol: &str, qty: Decimal, price: Decimal) -> Self {
+        Self::new(OrderType::Limit, Side::Sell, symbol, qty).limit_price(price)
+    }
+
+    /// A market buy spending `cash` of the quote currency.
+    pub fn market_buy_cash(symbol: &str, cash: Decimal) -> Self {
+        let mut order = Self::new(OrderType::Market, Side::Buy, symbol, None);
+        order.params.cash_order_qty = Some(cash);
+        order
+    }
+
+    /// Sells `qty` at market once the last trade reaches `trigger_price`.
+    pub fn stop_loss(symbol: &str, qty: Decimal, trigger_price: Decimal) -> Self {
+        Self::new(OrderType::StopLoss, Side::Sell, symbol, qty)
+            .triggers(Triggers::last(trigger_price))
+    }
+
+    /// Sells `qty` at market once the last trade reaches `trigger_price`.
+    pub fn take_profit(symbol: &str, qty: Decimal, trigger_price: Decimal) -> Self {
+        Self::new(OrderType::TakeProfit, Side::Sell, symbol, qty)
+            .triggers(Triggers::last(trigger_price))
+    }
+
+    pub fn limit_price(mut self, price: Decimal) -> Self {
+        self.params.limit_price = Some(price);
+        self
+    }
+
+    pub fn limit_price_type(mut self, price_type: PriceType) -> Self {
+        self.params.limit_price_type = Some(price_type);
+        self
+    }
+
+    pub fn triggers(mut self, triggers: Triggers) -> Self {
+        self.params.triggers = Some(triggers);
+        self
+    }
+
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.params.time_in_force = Some(time_in_force);
+        self
+    }
+
+    /// Expiry of a `Gtd` order, as an RFC 3339 timestamp.
+    pub fn expire_time(mut self, expire_time: impl Into<String>) -> Self {
+        self.params.expire_time = Some(expire_time.into());
+        self
+    }
+
+    pub fn post_only(mut self, post_only: bool) -> Self {
+        self.params.post_only = Some(post_only);
+        self
+    }
+
+    pub fn reduce_only(mut self, reduce_only: bool) -> Self {
+        self.params.reduce_only = Some(reduce_only);
+        self
+    }
+
+    pub fn margin(mut self, margin: bool) -> Self {
+        self.params.margin = Some(margin);
+        self
+    }
+
+    pub fn cl_ord_id(mut self, cl_ord_id: impl Into<String>) -> Self {
+        self.params.cl_ord_id = Some(cl_ord_id.into());
+        self
+    }
+
+    pub fn order_userref(mut self, order_userref: i64) -> Self {
+        self.params.order_userref = Some(order_userref);
+        self
+    }
+
+    pub fn display_qty(mut self, display_qty: Decimal) -> Self {
+        self.params.display_qty = Some(display_qty);
+        self
+    }
+
+    pub fn stp_type(mut self, stp_type: StpType) -> Self {
+        self.params.stp_type = Some(stp_type);
+        self
+    }
+
+    pub fn cash_order_qty(mut self, cash: Decimal) -> Self {
+        self.params.cash_order_qty = Some(cash);
+        self
+    }
+
+    pub fn fee_preference(mut self, fee_preference: FeePreference) -> Self {
+        self.params.fee_preference = Some(fee_preference);
+        self
+    }
+
+    pub fn validate(mut self, validate: bool) -> Self {
+        self.params.validate = Some(validate);
+        self
+    }
+}
+
+impl CancelOrderRequest {
+    pub fn new(order_id: &str) -> Self {
         Self {
-            method: "add_order".to_string(),
-            params: AddOrderParams {
-                ordertype: "limit".to_string(),
-                pair: pair.to_string(),
-                side: "buy".to_string(),
-                volume: volume.to_string(),
-                price: Some(price.to_string()),
+            method: "cancel_order".to_string(),
+            params: CancelOrderParams {
+                order_id: vec![order_id.to_string()],
                 token: None,
             },
+            req_id: None,
         }
     }
+}
+
+impl AmendOrderRequest {
+    pub fn new(order_id: impl Into<String>) -> Self {
+        Self::with_params(Some(order_id.into()), None)
+    }
+
+    /// Amends the order placed with this client order id.
+    pub fn by_cl_ord_id(cl_ord_id: impl Into<String>) -> Self {
+        Self::with_params(None, Some(cl_ord_id.into()))
+    }
 
-    pub fn market_sell(pair: &str, volume: &str) -> Self {
+    fn with_params(order_id: Option<String>, cl_ord_id: Option<String>) -> Self {
         Self {
-            method: "add_order".to_string(),
-            params: AddOrderParams {
-                ordertype: "market".to_string(),
-                pair: pair.to_string(),
-                side: "sell".to_string(),
-                volume: volume.to_string(),
-                price: None,
+            method: "amend_order".to_string(),
+            params: AmendOrderParams {
+                order_id,
+                cl_ord_id,
+                order_qty: None,
+                display_qty: None,
+                limit_price: None,
+                limit_price_type: None,
+                post_only: None,
+                trigger_price: None,
+                trigger_price_type: None,
+                deadline: None,
                 token: None,
             },
+            req_id: None,
         }
     }
 
-    pub fn limit_sell(pair: &str, volume: &str, price: &str) -> Self {
+    pub fn order_qty(mut self, qty: Decimal) -> Self {
+        self.params.order_qty = Some(qty);
+        self
+    }
+
+    pub fn display_qty(mut self, display_qty: Decimal) -> Self {
+        self.params.display_qty = Some(display_qty);
+        self
+    }
+
+    pub fn limit_price(mut self, price: Decimal) -> Self {
+        self.params.limit_price = Some(price);
+        self
+    }
+
+    pub fn limit_price_type(mut self, price_type: PriceType) -> Self {
+        self.params.limit_price_type = Some(price_type);
+        self
+    }
+
+    pub fn post_only(mut self, post_only: bool) -> Self {
+        self.params.post_only = Some(post_only);
+        self
+    }
+
+    pub fn trigger_price(mut self, price: Decimal) -> Self {
+        self.params.trigger_price = Some(price);
+        self
+    }
+
+    pub fn trigger_price_type(mut self, price_type: PriceType) -> Self {
+        self.params.trigger_price_type = Some(price_type);
+        self
+    }
+
+    pub fn deadline(mut self, deadline: impl Into<String>) -> Self {
+        self.params.deadline = Some(deadline.into());
+        self
+    }
+}
+
+impl EditOrderRequest {
+    pub fn new(order_id: impl Into<String>, symbol: impl Into<String>) -> Self {
         Self {
-            method: "add_order".to_string(),
-            params: AddOrderParams {
-                ordertype: "limit".to_string(),
-                pair: pair.to_string(),
-                side: "sell".to_string(),
-                volume: volume.to_string(),
-                price: Some(price.to_string()),
+            method: "edit_order".to_string(),
+            params: EditOrderParams {
+                order_id: order_id.into(),
+                symbol: symbol.into(),
+                order_qty: None,
+                display_qty: None,
+                limit_price: None,
+                triggers: None,
+                post_only: None,
+                reduce_only: None,
+                order_userref: None,
+                fee_preference: None,
+                deadline: None,
+                validate: None,
                 token: None,
             },
+            req_id: None,
         }
     }
+
+    pub fn order_qty(mut self, qty: Decimal) -> Self {
+        self.params.order_qty = Some(qty);
+        self
+    }
+
+    pub fn display_qty(mut self, display_qty: Decimal) -> Self {
+        self.params.display_qty = Some(display_qty);
+        self
+    }
+
+    pub fn limit_price(mut self, price: Decimal) -> Self {
+        self.params.limit_price = Some(price);
+        self
+    }
+
+    pub fn triggers(mut self, triggers: Triggers) -> Self {
+        self.params.triggers = Some(triggers);
+        self
+    }
+
+    pub fn post_only(mut self, post_only: bool) -> Self {
+        self.params.post_only = Some(post_only);
+        self
+    }
+
+    pub fn reduce_only(mut self, reduce_only: bool) -> Self {
+        self.params.reduce_only = Some(reduce_only);
+        self
+    }
+
+    pub fn order_userref(mut self, order_userref: i64) -> Self {
+        self.params.order_userref = Some(order_userref);
+        self
+    }
+
+    pub fn fee_preference(mut self, fee_preference: FeePreference) -> Self {
+        self.params.fee_preference = Some(fee_preference);
+        self
+    }
+
+    pub fn deadline(mut self, deadline: impl Into<String>) -> Self {
+        self.params.deadline = Some(deadline.into());
+        self
+    }
+
+    pub fn validate(mut self, validate: bool) -> Self {
+        self.params.validate = Some(validate);
+        self
+    }
 }
 
-impl CancelOrderRequest {
-    pub fn new(txid: &str) -> Self {
+impl CancelAllRequest {
+    pub fn new() -> Self {
         Self {
-            method: "cancel_order".to_string(),
-            params: CancelOrderParams {
-                txid: vec![txid.to_string()],
+            method: "cancel_all".to_string(),
+            params: TokenParams::default(),
+            req_id: None,
+        }
+    }
+}
+
+impl Default for CancelAllRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancelAllOrdersAfterRequest {
+    /// Rounded up to whole seconds, so only `Duration::ZERO` disarms the
+    /// switch.
+    pub fn new(timeout: std::time::Duration) -> Self {
+        let seconds = timeout.as_secs() + u64::from(timeout.subsec_nanos() > 0);
+        Self {
+            method: "cancel_all_orders_after".to_string(),
+            params: CancelAllOrdersAfterParams {
+                timeout: seconds,
                 token: None,
             },
+            req_id: None,
         }
     }
+
+    pub fn disarm() -> Self {
+        Self::new(std::time::Duration::ZERO)
+    }
+
+    /// Checks Kraken's limit, which `Client::cancel_all_orders_after` does
+    /// before sending: a timeout below `MAX_CANCEL_AFTER_SECS`.
+    pub fn check(&self) -> Result<(), Error> {
+        if self.params.timeout >= MAX_CANCEL_AFTER_SECS {
+            return Err(Error::InvalidTimeout(format!(
+                "{}s, expected less than {MAX_CANCEL_AFTER_SECS}s",
+                self.params.timeout
+            )));
+        }
+        Ok(())
+    }
 }
 
+/// Kraken's dead man's switch timeout must stay below a day.
+pub const MAX_CANCEL_AFTER_SECS: u64 = 86_400;
+
+/// Kraken's limits on the number of orders in a `batch_add`.
+pub const MIN_BATCH_ORDERS: usize = 2;
+pub const MAX_BATCH_ORDERS: usize = 15;
+/// Kraken's limit on the number of orders in a `batch_cancel`.
+pub const MAX_BATCH_CANCEL: usize = 50;
+
 impl BatchOrderRequest {
-    pub fn new(orders: Vec<AddOrderParams>) -> Self {
+    pub fn new(symbol: impl Into<String>, orders: Vec<AddOrderParams>) -> Self {
         Self {
             method: "batch_add".to_string(),
             params: BatchOrderParams {
+                symbol: symbol.into(),
                 orders,
+                deadline: None,
+                validate: None,
                 token: None,
             },
+            req_id: None,
         }
     }
 
+    /// A batch for the symbol of the first order.
     pub fn from_requests(requests: Vec<AddOrderRequest>) -> Self {
+        let symbol = requests
+            .first()
+            .map(|r| r.params.symbol.clone())
+            .unwrap_or_default();
         let orders = requests.into_iter().map(|r| r.params).collect();
-        Self::new(orders)
+        Self::new(symbol, orders)
+    }
+
+    pub fn deadline(mut self, deadline: impl Into<String>) -> Self {
+        self.params.deadline = Some(deadline.into());
+        self
+    }
+
+    pub fn validate(mut self, validate: bool) -> Self {
+        self.params.validate = Some(validate);
+        self
+    }
+
+    /// Checks Kraken's constraints, which `Client::batch_orders` does before
+    /// sending: 2 to 15 orders, all on the batch's symbol.
+    pub fn check(&self) -> Result<(), Error> {
+        let count = self.params.orders.len();
+        if !(MIN_BATCH_ORDERS..=MAX_BATCH_ORDERS).contains(&count) {
+            return Err(Error::InvalidBatch(format!(
+                "{count} orders, expected {MIN_BATCH_ORDERS} to {MAX_BATCH_ORDERS}"
+            )));
+        }
+        if self.params.symbol.is_empty() {
+            return Err(Error::InvalidBatch("no symbol".to_string()));
+        }
+        let other = self
+            .params
+            .orders
+            .iter()
+            .find(|order| !order.symbol.is_empty() && order.symbol != self.params.symbol);
+        if let Some(order) = other {
+            return Err(Error::InvalidBatch(format!(
+                "{} order in a {} batch",
+                order.symbol, self.params.symbol
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl BatchCancelRequest {
+    pub fn new() -> Self {
+        Self {
+            method: "batch_cancel".to_string(),
+            params: BatchCancelParams::default(),
+            req_id: None,
+        }
+    }
+
+    pub fn order_id(mut self, order_id: impl Into<String>) -> Self {
+        self.params.orders.push(order_id.into());
+        self
+    }
+
+    /// Cancels every order placed with this `order_userref`.
+    pub fn order_userref(mut self, order_userref: i64) -> Self {
+        self.params.orders.push(order_userref.to_string());
+        self
+    }
+
+    pub fn cl_ord_id(mut self, cl_ord_id: impl Into<String>) -> Self {
+        self.params.cl_ord_id.push(cl_ord_id.into());
+        self
+    }
+
+    /// Checks Kraken's constraints, which `Client::batch_cancel` does before
+    /// sending: 1 to 50 orders.
+    pub fn check(&self) -> Result<(), Error> {
+        let count = self.params.orders.len() + self.params.cl_ord_id.len();
+        if !(1..=MAX_BATCH_CANCEL).contains(&count) {
+            return Err(Error::InvalidBatch(format!(
+                "{count} orders to cancel, expected 1 to {MAX_BATCH_CANCEL}"
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for BatchCancelRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AddOrderResult {
+    pub order_id: String,
+    pub cl_ord_id: Option<String>,
+    pub order_userref: Option<i64>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CancelOrderResult {
+    pub order_id: String,
+    pub cl_ord_id: Option<String>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AmendOrderResult {
+    /// Identifies this amend in the order's `executions` reports.
+    pub amend_id: String,
+    pub order_id: Option<String>,
+    pub cl_ord_id: Option<String>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EditOrderResult {
+    /// Id of the replacement order.
+    pub order_id: String,
+    pub original_order_id: String,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+impl AddOrderResult {
+    /// One leg of a `batch_add` response: the placed order, or the reason
+    /// Kraken gave for rejecting that leg.
+    pub(crate) fn from_batch_leg(leg: serde_json::Value) -> crate::Result<Self> {
+        match leg.get("error").and_then(serde_json::Value::as_str) {
+            Some(message) if !message.is_empty() => Err(Error::Api {
+                method: "batch_add".to_string(),
+                message: message.to_string(),
+            }),
+            _ => serde_json::from_value(leg).map_err(|e| Error::Json(Box::new(e))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchCancelResult {
+    /// Number of orders canceled.
+    pub count: u64,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CancelAllResult {
+    /// Number of orders canceled.
+    pub count: u64,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CancelAllOrdersAfterResult {
+    #[serde(rename = "currentTime")]
+    pub current_time: String,
+    /// When the orders will be canceled; `0` once disarmed.
+    #[serde(rename = "triggerTime")]
+    pub trigger_time: String,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    #[test]
+    fn test_add_order_serializes_v2_schema() {
+        let order = AddOrderRequest::limit_buy("BTC/USD", dec!(0.5), dec!(42000.1))
+            .time_in_force(TimeInForce::Gtd)
+            .expire_time("2024-01-01T00:00:00Z")
+            .post_only(true)
+            .cl_ord_id("bot-1")
+            .order_userref(7)
+            .stp_type(StpType::CancelBoth)
+            .fee_preference(FeePreference::Quote)
+            .validate(true);
+
+        assert_eq!(
+            serde_json::to_value(&order).unwrap(),
+            json!({"method": "add_order", "params": {
+                "order_type": "limit", "side": "buy", "symbol": "BTC/USD",
+                "order_qty": 0.5, "limit_price": 42000.1,
+                "time_in_force": "gtd", "expire_time": "2024-01-01T00:00:00Z",
+                "post_only": true, "cl_ord_id": "bot-1", "order_userref": 7,
+                "stp_type": "cancel_both", "fee_preference": "quote", "validate": true
+            }})
+        );
+
+        let stop = AddOrderRequest::stop_loss("ETH/USD", dec!(1), dec!(1800));
+        assert_eq!(
+            serde_json::to_value(&stop.params).unwrap(),
+            json!({
+                "order_type": "stop-loss", "side": "sell", "symbol": "ETH/USD", "order_qty": 1,
+                "triggers": {"reference": "last", "price": 1800, "price_type": "static"}
+            })
+        );
+
+        let cash = AddOrderRequest::market_buy_cash("BTC/USD", dec!(100));
+        assert_eq!(
+            serde_json::to_value(&cash.params).unwrap(),
+            json!({"order_type": "market", "side": "buy", "symbol": "BTC/USD", "cash_order_qty": 100})
+        );
+    }
+
+    #[test]
+    fn test_batch_limits() {
+        let leg = |price| AddOrderRequest::limit_buy("BTC/USD", dec!(0.1), price);
+
+        let batch = BatchOrderRequest::from_requests(vec![leg(dec!(40000)), leg(dec!(39000))]);
+        assert!(batch.check().is_ok());
+        assert_eq!(
+            serde_json::to_value(&batch.params).unwrap(),
+            json!({"symbol": "BTC/USD", "orders": [
+                {"order_type": "limit", "side": "buy", "order_qty": 0.1, "limit_price": 40000},
+                {"order_type": "limit", "side": "buy", "order_qty": 0.1, "limit_price": 39000}
+            ]})
+        );
+
+        let single = BatchOrderRequest::from_requests(vec![leg(dec!(40000))]);
+        assert!(matches!(single.check(), Err(Error::InvalidBatch(_))));
+
+        let too_many = BatchOrderRequest::from_requests(vec![leg(dec!(40000)); 16]);
+        assert!(matches!(too_many.check(), Err(Error::InvalidBatch(_))));
+
+        let mixed = BatchOrderRequest::from_requests(vec![
+            leg(dec!(40000)),
+            AddOrderRequest::limit_sell("ETH/USD", dec!(1), dec!(3000)),
+        ]);
+        assert!(matches!(mixed.check(), Err(Error::InvalidBatch(_))));
+
+        assert!(BatchCancelRequest::new().check().is_err());
+        let cancel = BatchCancelRequest::new()
+            .order_id("OABC-123")
+            .order_userref(7)
+            .cl_ord_id("bot-1");
+        assert!(cancel.check().is_ok());
+        assert_eq!(
+            serde_json::to_value(&cancel.params).unwrap(),
+            json!({"orders": ["OABC-123", "7"], "cl_ord_id": ["bot-1"]})
+        );
+    }
+
+    #[test]
+    fn test_cancel_after_timeout() {
+        use std::time::Duration;
+
+        let short = CancelAllOrdersAfterRequest::new(Duration::from_millis(300));
+        assert_eq!(short.params.timeout, 1);
+        let partial = CancelAllOrdersAfterRequest::new(Duration::from_millis(60_500));
+        assert_eq!(partial.params.timeout, 61);
+        assert_eq!(CancelAllOrdersAfterRequest::disarm().params.timeout, 0);
+        assert!(CancelAllOrdersAfterRequest::disarm().check().is_ok());
+
+        let day = CancelAllOrdersAfterRequest::new(Duration::from_secs(MAX_CANCEL_AFTER_SECS));
+        assert!(matches!(day.check(), Err(Error::InvalidTimeout(_))));
+        let longest = Duration::from_secs(MAX_CANCEL_AFTER_SECS - 1);
+        assert!(CancelAllOrdersAfterRequest::new(longest).check().is_ok());
     }
 }
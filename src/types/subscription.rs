@@ -1,25 +1,170 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    Ticker,
+    Book,
+    Trade,
+    Ohlc,
+    Instrument,
+    Level3,
+    Executions,
+    Balances,
+}
+
+impl Channel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Channel::Ticker => "ticker",
+            Channel::Book => "book",
+            Channel::Trade => "trade",
+            Channel::Ohlc => "ohlc",
+            Channel::Instrument => "instrument",
+            Channel::Level3 => "level3",
+            Channel::Executions => "executions",
+            Channel::Balances => "balances",
+        }
+    }
+
+    /// Channels that need a WebSocket token.
+    pub fn is_private(&self) -> bool {
+        matches!(
+            self,
+            Channel::Level3 | Channel::Executions | Channel::Balances
+        )
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An active subscription as tracked by the client: one channel, one symbol.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Subscription {
+    pub channel: Channel,
+    pub symbol: Option<String>,
+}
 
 #[derive(Debug, Clone, Serialize)]
 pub struct SubscribeInput {
     pub method: String,
     pub params: SubscribeParams,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub req_id: Option<u64>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct SubscribeParams {
-    pub channel: String,
+    pub channel: Channel,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub symbol: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_trigger: Option<EventTrigger>,
+    /// WebSocket token for authenticated channels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Channel options without a field of their own, sent as given.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+/// What makes Kraken publish a ticker update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventTrigger {
+    /// Every change to the best bid or offer.
+    Bbo,
+    /// Every trade (Kraken's default).
+    Trades,
+}
+
+impl SubscribeParams {
+    /// Just the channel and symbols, with every option left to Kraken.
+    pub fn new(channel: Channel, symbols: Vec<String>) -> Self {
+        Self {
+            channel,
+            symbol: symbols,
+            depth: None,
+            snapshot: None,
+            interval: None,
+            event_trigger: None,
+            token: None,
+            extra: BTreeMap::new(),
+        }
+    }
 }
 
 impl SubscribeInput {
-    pub fn ticker(symbols: Vec<String>) -> Self {
+    pub fn subscribe(channel: Channel, symbols: Vec<String>) -> Self {
         Self {
             method: "subscribe".to_string(),
-            params: SubscribeParams {
-                channel: "ticker".to_string(),
-                symbol: symbols,
-            },
+            params: SubscribeParams::new(channel, symbols),
+            req_id: None,
+        }
+    }
+
+    pub fn unsubscribe(channel: Channel, symbols: Vec<String>) -> Self {
+        Self {
+            method: "unsubscribe".to_string(),
+            ..Self::subscribe(channel, symbols)
         }
     }
+
+    pub fn ticker(symbols: Vec<String>) -> Self {
+        Self::subscribe(Channel::Ticker, symbols)
+    }
+
+    pub fn trades(symbols: Vec<String>, snapshot: Option<bool>) -> Self {
+        let mut input = Self::subscribe(Channel::Trade, symbols);
+        input.params.snapshot = snapshot;
+        input
+    }
+
+    pub fn ohlc(symbols: Vec<String>, interval: Option<u32>, snapshot: Option<bool>) -> Self {
+        let mut input = Self::subscribe(Channel::Ohlc, symbols);
+        input.params.interval = interval;
+        input.params.snapshot = snapshot;
+        input
+    }
+
+    pub fn instruments(snapshot: Option<bool>) -> Self {
+        let mut input = Self::subscribe(Channel::Instrument, Vec::new());
+        input.params.snapshot = snapshot;
+        input
+    }
+
+    pub fn level3(symbols: Vec<String>, depth: Option<u32>, token: Option<String>) -> Self {
+        let mut input = Self::subscribe(Channel::Level3, symbols);
+        input.params.depth = depth;
+        input.params.token = token;
+        input
+    }
+
+    pub fn book(symbols: Vec<String>, depth: Option<u32>) -> Self {
+        let mut input = Self::subscribe(Channel::Book, symbols);
+        input.params.depth = depth;
+        input
+    }
+}
+
+/// Acknowledgement for one symbol of a `subscribe` or `unsubscribe` request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubscribeResult {
+    pub channel: Channel,
+    pub symbol: Option<String>,
+    pub snapshot: Option<bool>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
 }
@@ -1,12 +1,17 @@
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TickerData {
     pub symbol: String,
-    pub bid: f64,
-    pub ask: f64,
-    pub last: f64,
-    pub volume: f64,
+    #[serde(with = "crate::types::decimal::number")]
+    pub bid: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub ask: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub last: Decimal,
+    #[serde(with = "crate::types::decimal::number")]
+    pub volume: Decimal,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
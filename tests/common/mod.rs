@@ -0,0 +1,199 @@
+#![allow(dead_code)]
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::{accept_async, tungstenite::Message, WebSocketStream};
+
+pub type ServerSocket = WebSocketStream<TcpStream>;
+
+/// Minimal local stand-in for the Kraken WebSocket endpoint.
+pub struct MockServer {
+    listener: TcpListener,
+}
+
+impl MockServer {
+    pub async fn bind() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        Self { listener }
+    }
+
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.listener.local_addr().unwrap())
+    }
+
+    pub async fn accept(&self) -> ServerSocket {
+        let (stream, _) = tokio::time::timeout(Duration::from_secs(5), self.listener.accept())
+            .await
+            .expect("client did not connect")
+            .unwrap();
+        accept_async(stream).await.unwrap()
+    }
+}
+
+pub async fn recv_json(ws: &mut ServerSocket) -> Value {
+    loop {
+        let msg = tokio::time::timeout(Duration::from_secs(5), ws.next())
+            .await
+            .expect("no message from client")
+            .expect("socket closed")
+            .unwrap();
+        if let Message::Text(text) = msg {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+pub async fn send_json(ws: &mut ServerSocket, value: Value) {
+    send_text(ws, &value.to_string()).await;
+}
+
+pub async fn send_text(ws: &mut ServerSocket, text: &str) {
+    ws.send(Message::Text(text.to_string())).await.unwrap();
+}
+
+pub async fn send_binary(ws: &mut ServerSocket, text: &str) {
+    ws.send(Message::Binary(text.as_bytes().to_vec()))
+        .await
+        .unwrap();
+}
+
+/// Replies to a `subscribe`/`unsubscribe` request with one success per
+/// symbol, or a single one for channels that take no symbol.
+pub async fn ack_subscription(ws: &mut ServerSocket, request: &Value) {
+    let channel = &request["params"]["channel"];
+    let results = match request["params"]["symbol"].as_array() {
+        Some(symbols) => symbols
+            .iter()
+            .map(|symbol| serde_json::json!({"channel": channel, "symbol": symbol}))
+            .collect(),
+        None => vec![serde_json::json!({"channel": channel})],
+    };
+    for result in results {
+        send_json(
+            ws,
+            serde_json::json!({
+                "method": request["method"],
+                "req_id": request["req_id"],
+                "success": true,
+                "result": result,
+                "time_in": "2024-01-01T00:00:00.000000Z",
+                "time_out": "2024-01-01T00:00:00.000100Z"
+            }),
+        )
+        .await;
+    }
+}
+
+/// Receives the next request and acknowledges it as a subscription change.
+pub async fn recv_and_ack(ws: &mut ServerSocket) -> Value {
+    let request = recv_json(ws).await;
+    ack_subscription(ws, &request).await;
+    request
+}
+
+/// A request received by `MockRest`.
+#[derive(Debug, Clone)]
+pub struct RestRequest {
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RestRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Minimal local stand-in for Kraken's REST API: answers every request with
+/// the next of `replies` (repeating the last) and records what it received.
+pub struct MockRest {
+    addr: std::net::SocketAddr,
+    requests: std::sync::Arc<std::sync::Mutex<Vec<RestRequest>>>,
+}
+
+impl MockRest {
+    pub async fn start(replies: Vec<Value>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let recorded = recorded.clone();
+                let replies = replies.clone();
+                tokio::spawn(serve_rest(stream, recorded, replies));
+            }
+        });
+        Self { addr, requests }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn requests(&self) -> Vec<RestRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn serve_rest(
+    stream: TcpStream,
+    recorded: std::sync::Arc<std::sync::Mutex<Vec<RestRequest>>>,
+    replies: Vec<Value>,
+) {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+    let mut stream = BufReader::new(stream);
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line).await.unwrap_or(0) == 0 {
+            return;
+        }
+        let path = line.split_whitespace().nth(1).unwrap_or("").to_string();
+
+        let mut headers = Vec::new();
+        loop {
+            let mut header = String::new();
+            stream.read_line(&mut header).await.unwrap();
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        let length = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+            .map_or(0, |(_, value)| value.parse().unwrap());
+        let mut body = vec![0; length];
+        stream.read_exact(&mut body).await.unwrap();
+
+        let reply = {
+            let mut recorded = recorded.lock().unwrap();
+            recorded.push(RestRequest {
+                path,
+                headers,
+                body: String::from_utf8(body).unwrap(),
+            });
+            replies[(recorded.len() - 1).min(replies.len() - 1)].to_string()
+        };
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            reply.len(),
+            reply
+        );
+        stream
+            .get_mut()
+            .write_all(response.as_bytes())
+            .await
+            .unwrap();
+    }
+}
@@ -1,4 +1,5 @@
 use kraken_sdk::{Auth, Client, Config, RateLimiter};
+use rust_decimal_macros::dec;
 use std::time::Duration;
 
 #[tokio::test]
@@ -15,10 +16,10 @@ async fn test_client_creation() {
 #[test]
 fn test_auth_integration() {
     let auth = Auth::new("test_key".to_string(), "dGVzdF9zZWNyZXQ=".to_string());
-    let signature = auth.sign("test_message").unwrap();
+    let signature = auth.sign("/0/private/Balance", 1, "nonce=1").unwrap();
 
     // Test that signature is consistent
-    let signature2 = auth.sign("test_message").unwrap();
+    let signature2 = auth.sign("/0/private/Balance", 1, "nonce=1").unwrap();
     assert_eq!(signature, signature2);
 }
 
@@ -38,14 +39,25 @@ async fn test_rate_limiter() {
 
 #[test]
 fn test_order_types() {
-    use kraken_sdk::{AddOrderRequest, BatchOrderRequest};
+    use kraken_sdk::{AddOrderRequest, BatchOrderRequest, OrderType, Side};
 
-    let order = AddOrderRequest::market_buy("BTC/USD", "0.001");
-    assert_eq!(order.params.ordertype, "market");
-    assert_eq!(order.params.side, "buy");
+    let order = AddOrderRequest::market_buy("BTC/USD", dec!(0.001));
+    assert_eq!(order.params.order_type, OrderType::Market);
+    assert_eq!(order.params.side, Side::Buy);
+    let json = serde_json::to_string(&order).unwrap();
+    assert!(json.contains(r#""order_qty":0.001"#), "{}", json);
 
     let orders = vec![order];
     let batch = BatchOrderRequest::from_requests(orders);
     assert_eq!(batch.method, "batch_add");
     assert_eq!(batch.params.orders.len(), 1);
 }
+
+#[test]
+fn test_unrecognised_message_parses_as_unknown() {
+    use kraken_sdk::{parser::Parser, KrakenEvent};
+
+    let event = Parser::parse_standard(r#"{"type":"future","payload":{"id":1}}"#).unwrap();
+    assert!(matches!(event, KrakenEvent::Unknown(_)));
+    assert!(Parser::parse_standard("not json").is_err());
+}
@@ -0,0 +1,1497 @@
+mod common;
+
+use common::{recv_and_ack, recv_json, send_json, send_text, MockServer};
+use kraken_sdk::{Client, Config, KrakenEvent};
+use rust_decimal_macros::dec;
+use serde_json::json;
+use std::time::Duration;
+use tokio_stream::StreamExt;
+
+fn test_config(server: &MockServer) -> Config {
+    Config::builder()
+        .ws_url(server.url())
+        .max_retries(5)
+        .initial_backoff(Duration::from_millis(10))
+        .build()
+}
+
+fn ticker_message(symbol: &str) -> serde_json::Value {
+    json!({
+        "channel": "ticker",
+        "type": "update",
+        "data": [{
+            "symbol": symbol,
+            "bid": 42000.1, "bid_qty": 0.5, "ask": 42000.2, "ask_qty": 1.25,
+            "last": 42000.1, "volume": 1234.5, "vwap": 41900.0,
+            "low": 41000.0, "high": 43000.0, "change": 100.0, "change_pct": 0.24
+        }]
+    })
+}
+
+#[tokio::test]
+async fn test_reconnect_replays_subscriptions() {
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (stream, mut first) = tokio::join!(client.stream(), server.accept());
+    let mut stream = stream.unwrap();
+
+    let (result, sub) = tokio::join!(
+        client.subscribe_ticker().symbol("BTC/USD").send(),
+        recv_and_ack(&mut first)
+    );
+    assert_eq!(result.unwrap().len(), 1);
+    assert_eq!(sub["method"], "subscribe");
+    drop(first);
+
+    let mut second = server.accept().await;
+    let replayed = recv_json(&mut second).await;
+    assert_eq!(replayed["method"], "subscribe");
+    assert_eq!(replayed["params"], sub["params"]);
+
+    send_json(&mut second, ticker_message("BTC/USD")).await;
+    let ticker = tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(event) = stream.next().await {
+            if let KrakenEvent::Ticker(ticker) = event {
+                return ticker;
+            }
+        }
+        panic!("stream ended before the replayed subscription delivered data");
+    })
+    .await
+    .unwrap();
+    assert_eq!(ticker.data[0].symbol, "BTC/USD");
+}
+
+#[tokio::test]
+async fn test_connection_events_track_reconnect() {
+    use kraken_sdk::ConnectionState;
+
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let mut events = client.connection_events();
+
+    let (stream, first) = tokio::join!(client.stream(), server.accept());
+    let _stream = stream.unwrap();
+    assert_eq!(events.recv().await.unwrap(), ConnectionState::Connecting);
+    assert_eq!(events.recv().await.unwrap(), ConnectionState::Connected);
+    assert!(client.connection_state().is_connected());
+
+    drop(first);
+    let _second = server.accept().await;
+    assert!(matches!(
+        events.recv().await.unwrap(),
+        ConnectionState::Disconnected { .. }
+    ));
+    assert_eq!(
+        events.recv().await.unwrap(),
+        ConnectionState::Reconnecting { attempt: 1 }
+    );
+    assert_eq!(events.recv().await.unwrap(), ConnectionState::Connected);
+}
+
+#[tokio::test]
+async fn test_connection_events_report_give_up() {
+    use kraken_sdk::ConnectionState;
+
+    let url = MockServer::bind().await.url(); // listener dropped: nothing accepts
+    let config = Config::builder()
+        .ws_url(url)
+        .max_retries(1)
+        .initial_backoff(Duration::from_millis(10))
+        .build();
+    let mut client = Client::from_conf(config);
+    let mut events = client.connection_events();
+
+    assert!(client.connect().await.is_err());
+    assert_eq!(events.recv().await.unwrap(), ConnectionState::Connecting);
+    assert_eq!(
+        events.recv().await.unwrap(),
+        ConnectionState::Reconnecting { attempt: 1 }
+    );
+    assert_eq!(events.recv().await.unwrap(), ConnectionState::GaveUp);
+}
+
+#[tokio::test]
+async fn test_ping_records_round_trip() {
+    let server = MockServer::bind().await;
+    let config = Config::builder()
+        .ws_url(server.url())
+        .ping_interval(Duration::from_millis(20))
+        .build();
+    let mut client = Client::from_conf(config);
+
+    let (stream, mut ws) = tokio::join!(client.stream(), server.accept());
+    let mut stream = stream.unwrap();
+
+    let ping = recv_json(&mut ws).await;
+    assert_eq!(ping["method"], "ping");
+    send_json(
+        &mut ws,
+        json!({
+            "method": "pong",
+            "req_id": ping["req_id"],
+            "time_in": "2024-01-01T00:00:00.000000Z",
+            "time_out": "2024-01-01T00:00:00.000100Z"
+        }),
+    )
+    .await;
+
+    let event = tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(event, KrakenEvent::Pong(_)));
+    assert!(stream.metrics().rtt().is_some());
+}
+
+#[tokio::test]
+async fn test_silent_connection_triggers_reconnect() {
+    let server = MockServer::bind().await;
+    let config = Config::builder()
+        .ws_url(server.url())
+        .initial_backoff(Duration::from_millis(10))
+        .ping_interval(None)
+        .heartbeat_timeout(Duration::from_millis(100))
+        .build();
+    let mut client = Client::from_conf(config);
+
+    let (stream, _silent) = tokio::join!(client.stream(), server.accept());
+    let _stream = stream.unwrap();
+
+    // The first socket stays open but never speaks; the watchdog must give up on it.
+    let _second = server.accept().await;
+}
+
+#[tokio::test]
+async fn test_subscription_registry_dedups_and_unsubscribes() {
+    use kraken_sdk::{Channel, Subscription};
+
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (rx, mut ws) = tokio::join!(client.connect(), server.accept());
+    let _rx = rx.unwrap();
+
+    let server_side = async {
+        let mut requests = Vec::new();
+        for _ in 0..3 {
+            requests.push(recv_and_ack(&mut ws).await);
+        }
+        requests
+    };
+    let client_side = async {
+        client
+            .subscribe_ticker()
+            .symbol("BTC/USD")
+            .send()
+            .await
+            .unwrap();
+        client
+            .subscribe_ticker()
+            .symbol("BTC/USD")
+            .send()
+            .await
+            .unwrap();
+        client
+            .subscribe_ticker()
+            .symbol("ETH/USD")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(client.subscriptions().len(), 2);
+
+        client
+            .unsubscribe(Channel::Ticker)
+            .symbol("BTC/USD")
+            .send()
+            .await
+            .unwrap();
+    };
+    let ((), requests) = tokio::join!(client_side, server_side);
+
+    assert_eq!(
+        client.subscriptions(),
+        vec![Subscription {
+            channel: Channel::Ticker,
+            symbol: Some("ETH/USD".to_string()),
+        }]
+    );
+
+    assert_eq!(requests[0]["params"]["symbol"], json!(["BTC/USD"]));
+    assert_eq!(requests[1]["params"]["symbol"], json!(["ETH/USD"]));
+    assert_eq!(requests[2]["method"], "unsubscribe");
+    assert_eq!(
+        requests[2]["params"],
+        json!({"channel": "ticker", "symbol": ["BTC/USD"]})
+    );
+}
+
+#[tokio::test]
+async fn test_rejected_symbols_are_not_replayed() {
+    use kraken_sdk::{Channel, Error, Subscription};
+
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (rx, mut first) = tokio::join!(client.connect(), server.accept());
+    let _rx = rx.unwrap();
+
+    let server_side = async {
+        let request = recv_json(&mut first).await;
+        assert_eq!(request["params"]["symbol"], json!(["BTC/USD", "BAD/USD"]));
+        send_json(
+            &mut first,
+            json!({"method": "subscribe", "req_id": request["req_id"], "success": false,
+                   "error": "Currency pair not supported", "symbol": "BAD/USD"}),
+        )
+        .await;
+        send_json(
+            &mut first,
+            json!({"method": "subscribe", "req_id": request["req_id"], "success": true,
+                   "result": {"channel": "ticker", "symbol": "BTC/USD"}}),
+        )
+        .await;
+    };
+    let (result, ()) = tokio::join!(
+        client
+            .subscribe_ticker()
+            .symbols(vec!["BTC/USD".to_string(), "BAD/USD".to_string()])
+            .send(),
+        server_side
+    );
+    assert!(
+        matches!(result, Err(Error::Api { message, .. }) if message == "Currency pair not supported")
+    );
+    assert_eq!(
+        client.subscriptions(),
+        vec![Subscription {
+            channel: Channel::Ticker,
+            symbol: Some("BTC/USD".to_string()),
+        }]
+    );
+    drop(first);
+
+    let mut second = server.accept().await;
+    let replayed = recv_json(&mut second).await;
+    assert_eq!(replayed["params"]["symbol"], json!(["BTC/USD"]));
+}
+
+#[tokio::test]
+async fn test_add_order_resolves_matching_response() {
+    use kraken_sdk::AddOrderRequest;
+
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (rx, mut ws) = tokio::join!(client.connect(), server.accept());
+    let _rx = rx.unwrap();
+
+    let server_side = async {
+        let request = recv_json(&mut ws).await;
+        assert_eq!(request["method"], "add_order");
+        // An unrelated response first: it must not resolve this request.
+        send_json(
+            &mut ws,
+            json!({"method": "add_order", "req_id": 9999, "success": true,
+                   "result": {"order_id": "OTHER"}}),
+        )
+        .await;
+        send_json(
+            &mut ws,
+            json!({"method": "add_order", "req_id": request["req_id"], "success": true,
+                   "result": {"order_id": "OABC-123", "order_userref": 7}}),
+        )
+        .await;
+    };
+    let (result, ()) = tokio::join!(
+        client.add_order(AddOrderRequest::limit_buy(
+            "BTC/USD",
+            dec!(0.001),
+            dec!(40000)
+        )),
+        server_side
+    );
+    let result = result.unwrap();
+    assert_eq!(result.order_id, "OABC-123");
+    assert_eq!(result.order_userref, Some(7));
+}
+
+#[tokio::test]
+async fn test_request_errors_and_timeouts() {
+    use kraken_sdk::{CancelOrderRequest, Error};
+
+    let server = MockServer::bind().await;
+    let config = Config::builder()
+        .ws_url(server.url())
+        .request_timeout(Duration::from_millis(100))
+        .build();
+    let mut client = Client::from_conf(config);
+    let (rx, mut ws) = tokio::join!(client.connect(), server.accept());
+    let _rx = rx.unwrap();
+
+    let server_side = async {
+        let request = recv_json(&mut ws).await;
+        send_json(
+            &mut ws,
+            json!({"method": "cancel_order", "req_id": request["req_id"], "success": false,
+                   "error": "EOrder:Unknown order"}),
+        )
+        .await;
+    };
+    let (result, ()) = tokio::join!(
+        client.cancel_order(CancelOrderRequest::new("MISSING")),
+        server_side
+    );
+    match result {
+        Err(Error::Api { method, message }) => {
+            assert_eq!(method, "cancel_order");
+            assert_eq!(message, "EOrder:Unknown order");
+        }
+        other => panic!("expected API error, got {:?}", other),
+    }
+
+    let silent = client
+        .cancel_order(CancelOrderRequest::new("NO-REPLY"))
+        .await;
+    assert!(matches!(silent, Err(Error::Timeout)));
+}
+
+#[tokio::test]
+async fn test_subscriptions_before_connect_are_flushed() {
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+
+    let queued = client
+        .subscribe_ticker()
+        .symbol("BTC/USD")
+        .send()
+        .await
+        .unwrap();
+    assert!(queued.is_empty());
+    assert_eq!(client.subscriptions().len(), 1);
+
+    let (rx, mut ws) = tokio::join!(client.connect(), server.accept());
+    let _rx = rx.unwrap();
+    let flushed = recv_json(&mut ws).await;
+    assert_eq!(flushed["method"], "subscribe");
+    assert_eq!(flushed["params"]["symbol"], json!(["BTC/USD"]));
+}
+
+#[tokio::test]
+async fn test_orders_before_connect_fail() {
+    use kraken_sdk::{AddOrderRequest, Error};
+
+    let client = Client::from_conf(Config::builder().build());
+    let result = client
+        .add_order(AddOrderRequest::market_buy("BTC/USD", dec!(0.001)))
+        .await;
+    assert!(matches!(result, Err(Error::NotConnected)));
+}
+
+#[tokio::test]
+async fn test_unknown_messages_and_decode_errors_are_surfaced() {
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let mut decode_errors = client.decode_errors();
+    let (stream, mut ws) = tokio::join!(client.stream(), server.accept());
+    let mut stream = stream.unwrap();
+
+    send_text(&mut ws, "{not json").await;
+    send_json(&mut ws, json!({"channel": "brand_new", "data": [1, 2, 3]})).await;
+
+    let event = tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .unwrap()
+        .unwrap();
+    match event {
+        KrakenEvent::Unknown(value) => assert_eq!(value["channel"], "brand_new"),
+        other => panic!("expected unknown event, got {:?}", other),
+    }
+
+    let error = decode_errors.recv().await.unwrap();
+    assert_eq!(error.raw, "{not json");
+    assert_eq!(stream.metrics().decode_error_count(), 1);
+    assert_eq!(stream.metrics().unknown_count(), 1);
+}
+
+#[cfg(feature = "zero-copy")]
+#[tokio::test]
+async fn test_raw_stream_yields_borrowed_events() {
+    use kraken_sdk::BorrowedEvent;
+
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (stream, mut ws) = tokio::join!(client.raw_stream(), server.accept());
+    let mut stream = stream.unwrap();
+
+    let (result, _) = tokio::join!(
+        client.subscribe_ticker().symbol("BTC/USD").send(),
+        recv_and_ack(&mut ws)
+    );
+    assert_eq!(result.unwrap().len(), 1);
+
+    send_json(&mut ws, ticker_message("BTC/USD")).await;
+    let symbol = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let BorrowedEvent::Ticker(ticker) = stream.next().await.unwrap().unwrap() {
+                return ticker.data[0].symbol.to_string();
+            }
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(symbol, "BTC/USD");
+}
+
+/// Book message text with the number tokens written exactly as given, the
+/// way Kraken pads them to the instrument's precision.
+fn book_message(kind: &str, bids: &[(&str, &str)], asks: &[(&str, &str)], checksum: u32) -> String {
+    let levels = |side: &[(&str, &str)]| {
+        side.iter()
+            .map(|(price, qty)| format!(r#"{{"price":{},"qty":{}}}"#, price, qty))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    format!(
+        r#"{{"channel":"book","type":"{}","data":[{{"symbol":"BTC/USD","bids":[{}],"asks":[{}],"checksum":{},"timestamp":"2024-01-01T00:00:00.000000Z"}}]}}"#,
+        kind,
+        levels(bids),
+        levels(asks),
+        checksum
+    )
+}
+
+/// Kraken's checksum of a book, given its top levels best first.
+fn book_checksum(bids: &[(&str, &str)], asks: &[(&str, &str)]) -> u32 {
+    let digits: String = asks
+        .iter()
+        .chain(bids)
+        .flat_map(|(price, qty)| [price, qty])
+        .map(|number| number.replace('.', "").trim_start_matches('0').to_string())
+        .collect();
+    crc32fast::hash(digits.as_bytes())
+}
+
+async fn next_book_event(stream: &mut kraken_sdk::KrakenStream) -> KrakenEvent {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(event) = stream.next().await {
+            if let KrakenEvent::Orderbook(_) | KrakenEvent::BookResync(_) = event {
+                return event;
+            }
+        }
+        panic!("stream ended");
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_book_subscription_maintains_order_book() {
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (stream, mut ws) = tokio::join!(client.stream(), server.accept());
+    let mut stream = stream.unwrap();
+
+    let (result, request) = tokio::join!(
+        client.subscribe_book().symbol("BTC/USD").depth(25).send(),
+        recv_and_ack(&mut ws)
+    );
+    result.unwrap();
+    assert_eq!(
+        request["params"],
+        json!({"channel": "book", "symbol": ["BTC/USD"], "depth": 25})
+    );
+
+    let bids = [("42000.0", "1.50000000"), ("41999.5", "2.00000000")];
+    let asks = [("42000.5", "0.75000000")];
+    let checksum = book_checksum(&bids, &asks);
+    send_text(&mut ws, &book_message("snapshot", &bids, &asks, checksum)).await;
+
+    let removed = [("42000.0", "0.00000000")];
+    let checksum = book_checksum(&bids[1..], &asks);
+    send_text(&mut ws, &book_message("update", &removed, &[], checksum)).await;
+
+    next_book_event(&mut stream).await;
+    match next_book_event(&mut stream).await {
+        KrakenEvent::Orderbook(book) => {
+            assert_eq!(book.event_type, kraken_sdk::MessageType::Update)
+        }
+        other => panic!("expected the update, got {:?}", other),
+    }
+
+    let book = client.order_book("BTC/USD").unwrap();
+    assert_eq!(book.depth(), 25);
+    assert_eq!(book.best_bid().unwrap().price, dec!(41999.5));
+    assert_eq!(book.best_ask().unwrap().qty, dec!(0.75));
+    assert_eq!(book.spread(), Some(dec!(1.0)));
+}
+
+#[tokio::test]
+async fn test_book_checksum_mismatch_resubscribes() {
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (stream, mut ws) = tokio::join!(client.stream(), server.accept());
+    let mut stream = stream.unwrap();
+
+    let (result, _) = tokio::join!(
+        client.subscribe_book().symbol("BTC/USD").depth(25).send(),
+        recv_and_ack(&mut ws)
+    );
+    result.unwrap();
+
+    let bids = [("42000.0", "1.50000000")];
+    let asks = [("42000.5", "0.75000000")];
+    let checksum = book_checksum(&bids, &asks);
+    send_text(&mut ws, &book_message("snapshot", &bids, &asks, checksum)).await;
+    send_text(
+        &mut ws,
+        &book_message("update", &[("41999.0", "1.00000000")], &[], 12345),
+    )
+    .await;
+
+    next_book_event(&mut stream).await;
+    next_book_event(&mut stream).await;
+    match next_book_event(&mut stream).await {
+        KrakenEvent::BookResync(resync) => {
+            assert_eq!(resync.symbol, "BTC/USD");
+            assert_eq!(resync.expected, 12345);
+        }
+        other => panic!("expected a resync, got {:?}", other),
+    }
+    assert!(client.order_book("BTC/USD").is_none());
+
+    let params = json!({"channel": "book", "symbol": ["BTC/USD"], "depth": 25});
+    let unsubscribe = recv_json(&mut ws).await;
+    assert_eq!(unsubscribe["method"], "unsubscribe");
+    assert_eq!(unsubscribe["params"], params);
+    let subscribe = recv_json(&mut ws).await;
+    assert_eq!(subscribe["method"], "subscribe");
+    assert_eq!(subscribe["params"], params);
+    assert_eq!(client.subscriptions().len(), 1);
+
+    send_text(&mut ws, &book_message("snapshot", &bids, &asks, checksum)).await;
+    next_book_event(&mut stream).await;
+    assert_eq!(
+        client
+            .order_book("BTC/USD")
+            .unwrap()
+            .best_bid()
+            .unwrap()
+            .price,
+        dec!(42000.0)
+    );
+
+    // Failing again right away backs off before resubscribing.
+    send_text(
+        &mut ws,
+        &book_message("update", &[("41999.0", "1.00000000")], &[], 12345),
+    )
+    .await;
+    next_book_event(&mut stream).await;
+    assert!(matches!(
+        next_book_event(&mut stream).await,
+        KrakenEvent::BookResync(_)
+    ));
+    let early = tokio::time::timeout(Duration::from_millis(500), recv_json(&mut ws)).await;
+    assert!(early.is_err());
+    let unsubscribe = recv_json(&mut ws).await;
+    assert_eq!(unsubscribe["method"], "unsubscribe");
+}
+
+#[tokio::test]
+async fn test_every_book_frame_is_verified() {
+    use common::send_binary;
+
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let mut decode_errors = client.decode_errors();
+    let (stream, mut ws) = tokio::join!(client.stream(), server.accept());
+    let mut stream = stream.unwrap();
+
+    let (result, _) = tokio::join!(
+        client.subscribe_book().symbol("BTC/USD").send(),
+        recv_and_ack(&mut ws)
+    );
+    result.unwrap();
+
+    // Binary frames are checked like text ones.
+    let bids = [("42000.0", "1.50000000")];
+    let asks = [("42000.5", "0.75000000")];
+    send_binary(&mut ws, &book_message("snapshot", &bids, &asks, 12345)).await;
+    next_book_event(&mut stream).await;
+    assert!(matches!(
+        next_book_event(&mut stream).await,
+        KrakenEvent::BookResync(_)
+    ));
+    recv_json(&mut ws).await; // unsubscribe
+    recv_json(&mut ws).await; // subscribe
+
+    // Unpadded numbers leave the precision unknown: reported, not kept.
+    let bids = [("42000", "1.5")];
+    let asks = [("42000.5", "0.75")];
+    let checksum = book_checksum(&bids, &asks);
+    send_text(&mut ws, &book_message("snapshot", &bids, &asks, checksum)).await;
+    next_book_event(&mut stream).await;
+    let error = tokio::time::timeout(Duration::from_secs(5), decode_errors.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(error.error.contains("BTC/USD book dropped"));
+    assert!(client.order_book("BTC/USD").is_none());
+}
+
+#[tokio::test]
+async fn test_book_waiting_on_precision_resyncs_once_known() {
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let mut decode_errors = client.decode_errors();
+    let (stream, mut ws) = tokio::join!(client.stream(), server.accept());
+    let mut stream = stream.unwrap();
+
+    let (result, _) = tokio::join!(
+        client.subscribe_book().symbol("BTC/USD").send(),
+        recv_and_ack(&mut ws)
+    );
+    result.unwrap();
+
+    // Unpadded levels, and no instrument data yet to tell the precision.
+    let bids = [("42000", "1.5")];
+    let asks = [("42000.5", "0.75")];
+    let checksum = book_checksum(&[("42000.0", "1.50000000")], &[("42000.5", "0.75000000")]);
+    send_text(&mut ws, &book_message("snapshot", &bids, &asks, checksum)).await;
+    next_book_event(&mut stream).await;
+    let error = tokio::time::timeout(Duration::from_secs(5), decode_errors.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(error.error.contains("BTC/USD book dropped"));
+
+    let (result, _) = tokio::join!(client.subscribe_instruments().send(), recv_and_ack(&mut ws));
+    result.unwrap();
+    send_json(
+        &mut ws,
+        json!({"channel": "instrument", "type": "snapshot", "data": {
+            "assets": [],
+            "pairs": [{"symbol": "BTC/USD", "base": "BTC", "quote": "USD", "status": "online",
+                "qty_precision": 8, "qty_increment": 0.00000001, "price_precision": 1,
+                "cost_precision": 5, "marginable": true, "has_index": true, "cost_min": 0.5,
+                "tick_size": 0.1, "price_increment": 0.1, "qty_min": 0.0001}]
+        }}),
+    )
+    .await;
+
+    // The precision is known now, so the book is requested again.
+    let unsubscribe = recv_json(&mut ws).await;
+    assert_eq!(unsubscribe["method"], "unsubscribe");
+    assert_eq!(unsubscribe["params"]["channel"], "book");
+    let subscribe = recv_json(&mut ws).await;
+    assert_eq!(subscribe["method"], "subscribe");
+    assert_eq!(subscribe["params"]["symbol"], json!(["BTC/USD"]));
+
+    send_text(&mut ws, &book_message("snapshot", &bids, &asks, checksum)).await;
+    next_book_event(&mut stream).await;
+    let book = client.order_book("BTC/USD").unwrap();
+    assert_eq!(book.best_bid().unwrap().price, dec!(42000));
+    assert!(decode_errors.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_trade_subscription_streams_trades() {
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (stream, mut ws) = tokio::join!(client.stream(), server.accept());
+    let mut stream = stream.unwrap();
+
+    let (result, request) = tokio::join!(
+        client
+            .subscribe_trades()
+            .symbol("BTC/USD")
+            .snapshot(false)
+            .send(),
+        recv_and_ack(&mut ws)
+    );
+    assert_eq!(result.unwrap()[0].channel, kraken_sdk::Channel::Trade);
+    assert_eq!(
+        request["params"],
+        json!({"channel": "trade", "symbol": ["BTC/USD"], "snapshot": false})
+    );
+
+    send_json(
+        &mut ws,
+        json!({"channel": "trade", "type": "update", "data": [{
+            "symbol": "BTC/USD", "side": "sell", "price": 42000.1, "qty": 0.25,
+            "ord_type": "limit", "trade_id": 4665906, "timestamp": "2024-01-01T00:00:00.000000Z"
+        }]}),
+    )
+    .await;
+
+    let trade = tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(event) = stream.next().await {
+            if let KrakenEvent::Trade(trades) = event {
+                return trades;
+            }
+        }
+        panic!("stream ended");
+    })
+    .await
+    .unwrap();
+    assert_eq!(trade.data[0].side, "sell");
+    assert_eq!(trade.data[0].qty, dec!(0.25));
+}
+
+fn ohlc_message(kind: &str, interval_begin: &str, trades: u64) -> serde_json::Value {
+    json!({"channel": "ohlc", "type": kind, "data": [{
+        "symbol": "BTC/USD", "open": 42000.0, "high": 42100.0, "low": 41950.5,
+        "close": 42050.0, "vwap": 42020.3, "trades": trades, "volume": 3.25,
+        "interval_begin": interval_begin, "interval": 5,
+        "timestamp": "2024-01-01T00:05:00.000000Z"
+    }]})
+}
+
+#[tokio::test]
+async fn test_ohlc_subscription_reports_closed_candles() {
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (stream, mut ws) = tokio::join!(client.stream(), server.accept());
+    let mut stream = stream.unwrap();
+
+    let (result, request) = tokio::join!(
+        client.subscribe_ohlc().symbol("BTC/USD").interval(5).send(),
+        recv_and_ack(&mut ws)
+    );
+    result.unwrap();
+    assert_eq!(
+        request["params"],
+        json!({"channel": "ohlc", "symbol": ["BTC/USD"], "interval": 5})
+    );
+
+    let first = "2024-01-01T00:00:00.000000000Z";
+    let second = "2024-01-01T00:05:00.000000000Z";
+    send_json(&mut ws, ohlc_message("snapshot", first, 10)).await;
+    send_json(&mut ws, ohlc_message("update", first, 12)).await;
+    send_json(&mut ws, ohlc_message("update", second, 1)).await;
+
+    let mut candles = 0;
+    let closed = tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(event) = stream.next().await {
+            match event {
+                KrakenEvent::Ohlc(ohlc) => {
+                    assert_eq!(ohlc.data[0].interval, 5);
+                    candles += 1;
+                }
+                KrakenEvent::CandleClosed(candle) => return candle,
+                _ => {}
+            }
+        }
+        panic!("stream ended");
+    })
+    .await
+    .unwrap();
+    assert_eq!(candles, 3);
+    assert_eq!(closed.interval_begin, first);
+    assert_eq!(closed.trades, 12);
+    assert_eq!(closed.low, dec!(41950.5));
+}
+
+#[tokio::test]
+async fn test_instrument_subscription_fills_registry() {
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (stream, mut ws) = tokio::join!(client.stream(), server.accept());
+    let mut stream = stream.unwrap();
+
+    let (result, request) =
+        tokio::join!(client.subscribe_instruments().send(), recv_and_ack(&mut ws));
+    assert_eq!(result.unwrap().len(), 1);
+    assert_eq!(request["params"], json!({"channel": "instrument"}));
+
+    send_json(
+        &mut ws,
+        json!({"channel": "instrument", "type": "snapshot", "data": {
+            "assets": [{"id": "BTC", "status": "enabled", "precision": 10,
+                "precision_display": 5, "borrowable": true, "collateral_value": 1.0,
+                "margin_rate": 0.01}],
+            "pairs": [{"symbol": "BTC/USD", "base": "BTC", "quote": "USD", "status": "online",
+                "qty_precision": 8, "qty_increment": 0.00000001, "price_precision": 1,
+                "cost_precision": 5, "marginable": true, "has_index": true, "cost_min": 0.5,
+                "tick_size": 0.1, "price_increment": 0.1, "qty_min": 0.0001}]
+        }}),
+    )
+    .await;
+
+    // Book numbers arrive unpadded; the checksum still uses the pair's precision.
+    let (result, _) = tokio::join!(
+        client.subscribe_book().symbol("BTC/USD").send(),
+        recv_and_ack(&mut ws)
+    );
+    result.unwrap();
+    let checksum = book_checksum(&[("42000.0", "1.50000000")], &[("42000.5", "0.75000000")]);
+    send_json(
+        &mut ws,
+        json!({"channel": "book", "type": "snapshot", "data": [{
+            "symbol": "BTC/USD",
+            "bids": [{"price": 42000.0, "qty": 1.5}],
+            "asks": [{"price": 42000.5, "qty": 0.75}],
+            "checksum": checksum
+        }]}),
+    )
+    .await;
+
+    let mut seen_instruments = false;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(event) = stream.next().await {
+            match event {
+                KrakenEvent::Instrument(_) => seen_instruments = true,
+                KrakenEvent::Orderbook(_) => return,
+                KrakenEvent::BookResync(resync) => panic!("unexpected {:?}", resync),
+                _ => {}
+            }
+        }
+    })
+    .await
+    .unwrap();
+    assert!(seen_instruments);
+
+    let instruments = client.instruments();
+    let pair = instruments.pair("BTC/USD").unwrap();
+    assert_eq!(pair.price_increment, dec!(0.1));
+    assert_eq!(instruments.asset("BTC").unwrap().precision, 10);
+    let book = client.order_book("BTC/USD").unwrap();
+    assert_eq!(book.precision(), Some(pair.precision()));
+    assert_eq!(book.checksum(), Some(checksum));
+}
+
+#[tokio::test]
+async fn test_subscription_options_are_sent() {
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (stream, mut ws) = tokio::join!(client.stream(), server.accept());
+    let _stream = stream.unwrap();
+
+    let (result, request) = tokio::join!(
+        client
+            .subscribe_ticker()
+            .symbol("BTC/USD")
+            .event_trigger(kraken_sdk::EventTrigger::Bbo)
+            .snapshot(false)
+            .send(),
+        recv_and_ack(&mut ws)
+    );
+    result.unwrap();
+    assert_eq!(
+        request["params"],
+        json!({"channel": "ticker", "symbol": ["BTC/USD"], "event_trigger": "bbo", "snapshot": false})
+    );
+
+    let (result, request) = tokio::join!(
+        client
+            .subscribe_book()
+            .symbol("ETH/USD")
+            .param("snapshot", false)
+            .send(),
+        recv_and_ack(&mut ws)
+    );
+    result.unwrap();
+    assert_eq!(
+        request["params"],
+        json!({"channel": "book", "symbol": ["ETH/USD"], "snapshot": false})
+    );
+}
+
+#[tokio::test]
+async fn test_level3_subscription_maintains_order_queue() {
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (stream, mut ws) = tokio::join!(client.stream(), server.accept());
+    let mut stream = stream.unwrap();
+
+    let missing = client.subscribe_level3().symbol("BTC/USD").send().await;
+    assert!(matches!(missing, Err(kraken_sdk::Error::MissingToken)));
+
+    let (result, request) = tokio::join!(
+        client
+            .subscribe_level3()
+            .symbol("BTC/USD")
+            .depth(100)
+            .token("ws-token")
+            .send(),
+        recv_and_ack(&mut ws)
+    );
+    result.unwrap();
+    assert_eq!(
+        request["params"],
+        json!({"channel": "level3", "symbol": ["BTC/USD"], "depth": 100, "token": "ws-token"})
+    );
+
+    let order = |event: Option<&str>, id: &str, price: f64, qty: f64| {
+        let mut order = json!({"order_id": id, "limit_price": price, "order_qty": qty,
+            "timestamp": "2024-01-01T00:00:00.000000000Z"});
+        if let Some(event) = event {
+            order["event"] = json!(event);
+        }
+        order
+    };
+    send_json(
+        &mut ws,
+        json!({"channel": "level3", "type": "snapshot", "data": [{
+            "symbol": "BTC/USD", "checksum": 0,
+            "bids": [order(None, "OA", 42000.0, 1.0), order(None, "OB", 42000.0, 2.0)],
+            "asks": [order(None, "OC", 42000.5, 0.5)]
+        }]}),
+    )
+    .await;
+    send_json(
+        &mut ws,
+        json!({"channel": "level3", "type": "update", "data": [{
+            "symbol": "BTC/USD", "checksum": 0,
+            "bids": [order(Some("delete"), "OA", 42000.0, 1.0), order(Some("add"), "OD", 42000.0, 0.25)],
+            "asks": []
+        }]}),
+    )
+    .await;
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(event) = stream.next().await {
+            if let KrakenEvent::Level3(level3) = event {
+                if level3.event_type == kraken_sdk::MessageType::Update {
+                    return;
+                }
+            }
+        }
+    })
+    .await
+    .unwrap();
+
+    let book = client.level3_book("BTC/USD").unwrap();
+    assert_eq!(book.queue_position("OB").unwrap().orders_ahead, 0);
+    assert_eq!(book.queue_position("OD").unwrap().qty_ahead, dec!(2.0));
+    assert_eq!(book.bid_levels()[0].qty, dec!(2.25));
+    assert_eq!(book.to_order_book(10).spread(), Some(dec!(0.5)));
+}
+
+#[tokio::test]
+async fn test_level3_needs_its_endpoint() {
+    use kraken_sdk::{Auth, Error};
+
+    // Credentials point the client at ws-auth, which does not serve level3.
+    let config = Config::builder()
+        .auth(Auth::new("key".to_string(), "c2VjcmV0".to_string()))
+        .build();
+    let client = Client::from_conf(config);
+    let result = client.subscribe_level3().symbol("BTC/USD").send().await;
+    match result {
+        Err(Error::WrongEndpoint {
+            channel,
+            url,
+            expected,
+        }) => {
+            assert_eq!(channel, "level3");
+            assert_eq!(url, kraken_sdk::config::AUTH_WS_URL);
+            assert_eq!(expected, kraken_sdk::config::LEVEL3_WS_URL);
+        }
+        other => panic!("expected WrongEndpoint, got {:?}", other),
+    }
+    assert!(client.subscriptions().is_empty());
+}
+
+#[tokio::test]
+async fn test_executions_track_open_orders() {
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (stream, mut ws) = tokio::join!(client.stream(), server.accept());
+    let mut stream = stream.unwrap();
+
+    let (result, request) = tokio::join!(
+        client
+            .subscribe_executions()
+            .token("ws-token")
+            .snap_trades(true)
+            .send(),
+        recv_and_ack(&mut ws)
+    );
+    result.unwrap();
+    assert_eq!(
+        request["params"],
+        json!({"channel": "executions", "token": "ws-token", "snap_trades": true})
+    );
+
+    send_json(
+        &mut ws,
+        json!({"channel": "executions", "type": "snapshot", "sequence": 1, "data": [
+            {"order_id": "OA", "exec_type": "new", "order_status": "new", "symbol": "BTC/USD",
+             "side": "buy", "order_type": "limit", "order_qty": 1.0, "limit_price": 42000.0,
+             "cl_ord_id": "bot-1", "order_userref": 7, "timestamp": "2024-01-01T00:00:00Z"},
+            {"order_id": "OB", "exec_type": "new", "order_status": "new", "symbol": "BTC/USD",
+             "side": "sell", "order_type": "limit", "order_qty": 2.0, "limit_price": 43000.0,
+             "timestamp": "2024-01-01T00:00:00Z"},
+            {"order_id": "OZ", "exec_type": "trade", "exec_id": "TZ", "last_qty": 0.1,
+             "last_price": 41000.0, "timestamp": "2023-12-31T00:00:00Z"}
+        ]}),
+    )
+    .await;
+    send_json(
+        &mut ws,
+        json!({"channel": "executions", "type": "update", "sequence": 2, "data": [
+            {"order_id": "OA", "exec_type": "trade", "order_status": "partially_filled",
+             "exec_id": "TA", "last_qty": 0.25, "last_price": 42000.0, "cum_qty": 0.25,
+             "liquidity_ind": "t", "fees": [{"asset": "USD", "qty": 2.73}],
+             "timestamp": "2024-01-01T00:00:01Z"},
+            {"order_id": "OB", "exec_type": "canceled", "order_status": "canceled",
+             "reason": "User requested", "timestamp": "2024-01-01T00:00:01Z"}
+        ]}),
+    )
+    .await;
+
+    let fill = tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(event) = stream.next().await {
+            if let KrakenEvent::Executions(executions) = event {
+                if executions.event_type == kraken_sdk::MessageType::Update {
+                    return executions;
+                }
+            }
+        }
+        panic!("stream ended");
+    })
+    .await
+    .unwrap();
+    assert_eq!(fill.data[0].exec_type, kraken_sdk::ExecType::Trade);
+    assert_eq!(
+        fill.data[0].liquidity_ind,
+        Some(kraken_sdk::Liquidity::Taker)
+    );
+
+    let open = client.open_orders();
+    assert_eq!(open.len(), 1);
+    let order = client.open_order("OA").unwrap();
+    assert_eq!(
+        order.order_status,
+        Some(kraken_sdk::OrderStatus::PartiallyFilled)
+    );
+    assert_eq!(order.order_userref, Some(7));
+    assert_eq!(order.cum_qty, Some(dec!(0.25)));
+    assert_eq!(order.limit_price, Some(dec!(42000.0)));
+
+    // Unsubscribing a private channel needs a token just like subscribing.
+    let missing = client
+        .unsubscribe(kraken_sdk::Channel::Executions)
+        .send()
+        .await;
+    assert!(matches!(missing, Err(kraken_sdk::Error::MissingToken)));
+    assert_eq!(client.open_orders().len(), 1);
+
+    let (result, request) = tokio::join!(
+        client
+            .unsubscribe(kraken_sdk::Channel::Executions)
+            .token("ws-token-2")
+            .send(),
+        recv_and_ack(&mut ws)
+    );
+    result.unwrap();
+    assert_eq!(request["method"], "unsubscribe");
+    assert_eq!(request["params"]["token"], "ws-token-2");
+    assert!(client.open_orders().is_empty());
+}
+
+#[tokio::test]
+async fn test_balances_track_ledger_updates() {
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (stream, mut ws) = tokio::join!(client.stream(), server.accept());
+    let mut stream = stream.unwrap();
+
+    let (result, request) = tokio::join!(
+        client.subscribe_balances().token("ws-token").send(),
+        recv_and_ack(&mut ws)
+    );
+    result.unwrap();
+    assert_eq!(
+        request["params"],
+        json!({"channel": "balances", "token": "ws-token"})
+    );
+
+    send_json(
+        &mut ws,
+        json!({"channel": "balances", "type": "snapshot", "sequence": 1, "data": [
+            {"asset": "USD", "asset_class": "currency", "balance": 5000.0, "wallets": [
+                {"type": "spot", "id": "main", "balance": 5000.0}]},
+            {"asset": "BTC", "asset_class": "currency", "balance": 0.5, "wallets": [
+                {"type": "spot", "id": "main", "balance": 0.5}]}
+        ]}),
+    )
+    .await;
+    send_json(
+        &mut ws,
+        json!({"channel": "balances", "type": "update", "sequence": 2, "data": [
+            {"ledger_id": "L-1", "ref_id": "T-1", "timestamp": "2024-01-01T00:00:01Z",
+             "type": "trade", "subtype": "", "asset": "USD", "asset_class": "currency",
+             "wallet_type": "spot", "wallet_id": "main", "amount": -2100.0, "fee": 5.46,
+             "balance": 2894.54}
+        ]}),
+    )
+    .await;
+
+    let update = tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(event) = stream.next().await {
+            if let KrakenEvent::Balances(balances) = event {
+                if let kraken_sdk::BalancesData::Update(entries) = balances.data {
+                    return entries;
+                }
+            }
+        }
+        panic!("stream ended");
+    })
+    .await
+    .unwrap();
+    assert_eq!(update[0].ledger_id, "L-1");
+    assert_eq!(update[0].fee, dec!(5.46));
+
+    assert_eq!(client.balances().len(), 2);
+    assert_eq!(client.balance("USD").unwrap().balance, dec!(2894.54));
+    assert_eq!(client.balance("BTC").unwrap().balance, dec!(0.5));
+}
+
+#[tokio::test]
+async fn test_private_requests_carry_provider_token() {
+    use common::MockRest;
+    use kraken_sdk::{AddOrderRequest, Auth, TokenProvider};
+
+    let rest = MockRest::start(vec![
+        json!({"error": [], "result": {"token": "token-1", "expires": 900}}),
+        json!({"error": [], "result": {"token": "token-2", "expires": 900}}),
+    ])
+    .await;
+    let server = MockServer::bind().await;
+    let auth = Auth::new("key".to_string(), "c2VjcmV0".to_string());
+    let config = Config::builder()
+        .ws_url(server.url())
+        .token_provider(TokenProvider::new(auth).base_url(rest.url()))
+        .build();
+    let mut client = Client::from_conf(config);
+    let (rx, mut ws) = tokio::join!(client.connect(), server.accept());
+    let _rx = rx.unwrap();
+
+    let (result, request) = tokio::join!(client.subscribe_balances().send(), recv_and_ack(&mut ws));
+    result.unwrap();
+    assert_eq!(request["params"]["token"], "token-1");
+
+    let (result, request) = tokio::join!(
+        client.subscribe_executions().token("explicit").send(),
+        recv_and_ack(&mut ws)
+    );
+    result.unwrap();
+    assert_eq!(request["params"]["token"], "explicit");
+
+    // An expired token is replaced and the order sent again.
+    let server_side = async {
+        let first = recv_json(&mut ws).await;
+        assert_eq!(first["params"]["token"], "token-1");
+        send_json(
+            &mut ws,
+            json!({"method": "add_order", "req_id": first["req_id"], "success": false,
+                   "error": "EAPI:Invalid token"}),
+        )
+        .await;
+        let retry = recv_json(&mut ws).await;
+        assert_eq!(retry["params"]["token"], "token-2");
+        assert_ne!(retry["req_id"], first["req_id"]);
+        send_json(
+            &mut ws,
+            json!({"method": "add_order", "req_id": retry["req_id"], "success": true,
+                   "result": {"order_id": "OABC-123"}}),
+        )
+        .await;
+    };
+    let (result, ()) = tokio::join!(
+        client.add_order(AddOrderRequest::limit_buy(
+            "BTC/USD",
+            dec!(0.001),
+            dec!(40000)
+        )),
+        server_side
+    );
+    assert_eq!(result.unwrap().order_id, "OABC-123");
+    assert_eq!(rest.requests().len(), 2);
+
+    // Replayed after a reconnect with the current token, even where one was
+    // passed explicitly.
+    drop(ws);
+    let mut ws = server.accept().await;
+    let mut channels = Vec::new();
+    for _ in 0..2 {
+        let replay = recv_json(&mut ws).await;
+        assert_eq!(replay["params"]["token"], "token-2");
+        channels.push(replay["params"]["channel"].as_str().unwrap().to_string());
+    }
+    channels.sort();
+    assert_eq!(channels, ["balances", "executions"]);
+
+    // Unsubscribing uses the current token too, not the explicit one.
+    let (result, request) = tokio::join!(
+        client.unsubscribe(kraken_sdk::Channel::Executions).send(),
+        recv_and_ack(&mut ws)
+    );
+    result.unwrap();
+    assert_eq!(request["method"], "unsubscribe");
+    assert_eq!(request["params"]["token"], "token-2");
+}
+
+#[tokio::test]
+async fn test_amend_and_edit_order() {
+    use kraken_sdk::{AmendOrderRequest, EditOrderRequest};
+
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (rx, mut ws) = tokio::join!(client.connect(), server.accept());
+    let _rx = rx.unwrap();
+
+    let server_side = async {
+        let request = recv_json(&mut ws).await;
+        assert_eq!(request["method"], "amend_order");
+        assert_eq!(
+            request["params"],
+            json!({"cl_ord_id": "bot-1", "order_qty": 0.75, "limit_price": 41999.5, "post_only": true})
+        );
+        send_json(
+            &mut ws,
+            json!({"method": "amend_order", "req_id": request["req_id"], "success": true,
+                   "result": {"amend_id": "TTW6PD-RC36L-ZD3W5A", "cl_ord_id": "bot-1"}}),
+        )
+        .await;
+
+        let request = recv_json(&mut ws).await;
+        assert_eq!(request["method"], "edit_order");
+        assert_eq!(
+            request["params"],
+            json!({"order_id": "OABC-123", "symbol": "BTC/USD", "order_qty": 1.5})
+        );
+        send_json(
+            &mut ws,
+            json!({"method": "edit_order", "req_id": request["req_id"], "success": true,
+                   "result": {"order_id": "ODEF-456", "original_order_id": "OABC-123"}}),
+        )
+        .await;
+    };
+    let client_side = async {
+        let amended = client
+            .amend_order(
+                AmendOrderRequest::by_cl_ord_id("bot-1")
+                    .order_qty(dec!(0.75))
+                    .limit_price(dec!(41999.5))
+                    .post_only(true),
+            )
+            .await
+            .unwrap();
+        assert_eq!(amended.amend_id, "TTW6PD-RC36L-ZD3W5A");
+        assert_eq!(amended.cl_ord_id.as_deref(), Some("bot-1"));
+
+        let edited = client
+            .edit_order(EditOrderRequest::new("OABC-123", "BTC/USD").order_qty(dec!(1.5)))
+            .await
+            .unwrap();
+        assert_eq!(edited.order_id, "ODEF-456");
+        assert_eq!(edited.original_order_id, "OABC-123");
+    };
+    tokio::join!(client_side, server_side);
+}
+
+#[tokio::test]
+async fn test_cancel_all() {
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (rx, mut ws) = tokio::join!(client.connect(), server.accept());
+    let _rx = rx.unwrap();
+
+    let server_side = async {
+        let request = recv_json(&mut ws).await;
+        assert_eq!(request["method"], "cancel_all");
+        send_json(
+            &mut ws,
+            json!({"method": "cancel_all", "req_id": request["req_id"], "success": true,
+                   "result": {"count": 3}}),
+        )
+        .await;
+    };
+    let (result, ()) = tokio::join!(client.cancel_all(), server_side);
+    assert_eq!(result.unwrap().count, 3);
+}
+
+#[tokio::test]
+async fn test_batch_orders_resolve_per_order() {
+    use kraken_sdk::{AddOrderRequest, BatchCancelRequest, BatchOrderRequest, Error};
+
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (rx, mut ws) = tokio::join!(client.connect(), server.accept());
+    let _rx = rx.unwrap();
+
+    let batch = BatchOrderRequest::from_requests(vec![
+        AddOrderRequest::limit_buy("BTC/USD", dec!(0.1), dec!(40000)),
+        AddOrderRequest::limit_buy("BTC/USD", dec!(0.1), dec!(1)),
+    ]);
+    let server_side = async {
+        let request = recv_json(&mut ws).await;
+        assert_eq!(request["method"], "batch_add");
+        assert_eq!(request["params"]["symbol"], "BTC/USD");
+        assert!(request["params"]["orders"][0].get("symbol").is_none());
+        send_json(
+            &mut ws,
+            json!({"method": "batch_add", "req_id": request["req_id"], "success": true,
+                   "result": [{"order_id": "OA-1"}, {"error": "EOrder:Invalid price"}]}),
+        )
+        .await;
+    };
+    let (results, ()) = tokio::join!(client.batch_orders(batch), server_side);
+    let results = results.unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap().order_id, "OA-1");
+    assert!(
+        matches!(&results[1], Err(Error::Api { message, .. }) if message == "EOrder:Invalid price")
+    );
+
+    // Batches breaking Kraken's limits are not sent.
+    let mixed = BatchOrderRequest::from_requests(vec![
+        AddOrderRequest::limit_buy("BTC/USD", dec!(0.1), dec!(40000)),
+        AddOrderRequest::limit_sell("ETH/USD", dec!(1), dec!(3000)),
+    ]);
+    assert!(matches!(
+        client.batch_orders(mixed).await,
+        Err(Error::InvalidBatch(_))
+    ));
+    assert!(matches!(
+        client.batch_cancel(BatchCancelRequest::new()).await,
+        Err(Error::InvalidBatch(_))
+    ));
+    let nothing = tokio::time::timeout(Duration::from_millis(100), recv_json(&mut ws)).await;
+    assert!(nothing.is_err());
+}
+
+#[tokio::test]
+async fn test_batch_cancel() {
+    use kraken_sdk::BatchCancelRequest;
+
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (rx, mut ws) = tokio::join!(client.connect(), server.accept());
+    let _rx = rx.unwrap();
+
+    let batch = BatchCancelRequest::new()
+        .order_id("OA-1")
+        .order_userref(42)
+        .cl_ord_id("grid-1");
+    let server_side = async {
+        let request = recv_json(&mut ws).await;
+        assert_eq!(request["method"], "batch_cancel");
+        assert_eq!(
+            request["params"],
+            json!({"orders": ["OA-1", "42"], "cl_ord_id": ["grid-1"]})
+        );
+        send_json(
+            &mut ws,
+            json!({"method": "batch_cancel", "req_id": request["req_id"], "success": true,
+                   "result": {"count": 3}}),
+        )
+        .await;
+    };
+    let (result, ()) = tokio::join!(client.batch_cancel(batch), server_side);
+    assert_eq!(result.unwrap().count, 3);
+}
+
+#[tokio::test]
+async fn test_dead_man_switch_refreshes_while_connected() {
+    use kraken_sdk::Error;
+
+    let server = MockServer::bind().await;
+    let mut client = Client::from_conf(test_config(&server));
+    let (rx, mut ws) = tokio::join!(client.connect(), server.accept());
+    let _rx = rx.unwrap();
+
+    for (timeout, refresh_every) in [(60, 0), (60, 60), (0, 0), (86_400, 15)] {
+        let invalid = client.start_dead_man_switch(
+            Duration::from_secs(timeout),
+            Duration::from_secs(refresh_every),
+        );
+        assert!(matches!(invalid, Err(Error::InvalidTimeout(_))));
+    }
+
+    let switch = client
+        .start_dead_man_switch(Duration::from_secs(60), Duration::from_millis(50))
+        .unwrap();
+    for refresh in 0..4 {
+        let request = recv_json(&mut ws).await;
+        assert_eq!(request["method"], "cancel_all_orders_after");
+        assert_eq!(request["params"], json!({"timeout": 60}));
+        // Each refresh waits for the previous one to be answered.
+        match refresh {
+            2 => assert!(switch
+                .last_error()
+                .unwrap()
+                .contains("EService:Unavailable")),
+            3 => assert_eq!(switch.last_error(), None),
+            _ => {}
+        }
+        let reply = if refresh == 1 {
+            json!({"method": "cancel_all_orders_after", "req_id": request["req_id"],
+                   "success": false, "error": "EService:Unavailable"})
+        } else {
+            json!({"method": "cancel_all_orders_after", "req_id": request["req_id"],
+                   "success": true, "result": {"currentTime": "2024-01-01T00:00:00Z",
+                                               "triggerTime": "2024-01-01T00:01:00Z"}})
+        };
+        send_json(&mut ws, reply).await;
+    }
+
+    let server_side = async {
+        loop {
+            let request = recv_json(&mut ws).await;
+            if request["params"]["timeout"] == 0 {
+                send_json(
+                    &mut ws,
+                    json!({"method": "cancel_all_orders_after", "req_id": request["req_id"],
+                           "success": true, "result": {"currentTime": "2024-01-01T00:00:01Z",
+                                                       "triggerTime": "0"}}),
+                )
+                .await;
+                return ws;
+            }
+        }
+    };
+    let (result, mut ws) = tokio::join!(switch.disarm(), server_side);
+    assert_eq!(result.unwrap().trigger_time, "0");
+
+    // Nothing more once disarmed.
+    let more = tokio::time::timeout(Duration::from_millis(200), recv_json(&mut ws)).await;
+    assert!(more.is_err());
+}
+
+#[tokio::test]
+async fn test_dead_man_switch_reports_skipped_refreshes() {
+    let server = MockServer::bind().await;
+    let config = Config::builder()
+        .ws_url(server.url())
+        .auto_reconnect(false)
+        .build();
+    let mut client = Client::from_conf(config);
+    let (rx, mut ws) = tokio::join!(client.connect(), server.accept());
+    let _rx = rx.unwrap();
+
+    let switch = client
+        .start_dead_man_switch(Duration::from_secs(60), Duration::from_millis(50))
+        .unwrap();
+    let request = recv_json(&mut ws).await;
+    send_json(
+        &mut ws,
+        json!({"method": "cancel_all_orders_after", "req_id": request["req_id"],
+               "success": true, "result": {"currentTime": "2024-01-01T00:00:00Z",
+                                           "triggerTime": "2024-01-01T00:01:00Z"}}),
+    )
+    .await;
+    recv_json(&mut ws).await; // The next refresh proves the first was handled
+    assert_eq!(switch.last_error(), None);
+
+    // Refreshes stop with the connection, and the handle says so.
+    drop(ws);
+    let skipped = tokio::time::timeout(Duration::from_secs(5), async {
+        while switch.last_error().as_deref() != Some("not connected") {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await;
+    assert!(skipped.is_ok());
+}
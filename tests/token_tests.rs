@@ -0,0 +1,93 @@
+mod common;
+
+use common::MockRest;
+use kraken_sdk::{Auth, Error, TokenProvider};
+use serde_json::json;
+use std::time::Duration;
+
+const SECRET: &str =
+    "kQH5HW/8p1uGOVjbgWA7FunAmGO8lsSUXNsu3eow76sz84Q18fWxnyRzBHCd3pd5nE9qa99HAZtuZuj6F1huXg==";
+
+fn token_reply(token: &str, expires: u64) -> serde_json::Value {
+    json!({"error": [], "result": {"token": token, "expires": expires}})
+}
+
+#[tokio::test]
+async fn test_token_request_is_signed() {
+    let rest = MockRest::start(vec![token_reply("token-1", 900)]).await;
+    let auth = Auth::new("api-key".to_string(), SECRET.to_string());
+    let provider = TokenProvider::new(auth.clone()).base_url(rest.url());
+
+    assert_eq!(provider.token().await.unwrap(), "token-1");
+
+    let requests = rest.requests();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request.path, "/0/private/GetWebSocketsToken");
+    assert_eq!(request.header("API-Key"), Some("api-key"));
+
+    let nonce: u64 = request
+        .body
+        .strip_prefix("nonce=")
+        .unwrap()
+        .parse()
+        .unwrap();
+    let expected = auth
+        .sign("/0/private/GetWebSocketsToken", nonce, &request.body)
+        .unwrap();
+    assert_eq!(request.header("API-Sign"), Some(expected.as_str()));
+}
+
+#[tokio::test]
+async fn test_token_is_cached_until_close_to_expiry() {
+    let rest = MockRest::start(vec![
+        token_reply("token-1", 900),
+        token_reply("token-2", 900),
+    ])
+    .await;
+    let provider =
+        TokenProvider::new(Auth::new("key".to_string(), SECRET.to_string())).base_url(rest.url());
+
+    assert_eq!(provider.token().await.unwrap(), "token-1");
+    assert_eq!(provider.token().await.unwrap(), "token-1");
+    assert_eq!(rest.requests().len(), 1);
+
+    assert_eq!(provider.refresh().await.unwrap(), "token-2");
+    assert_eq!(provider.token().await.unwrap(), "token-2");
+    assert_eq!(rest.requests().len(), 2);
+
+    let nonces: Vec<u64> = rest
+        .requests()
+        .iter()
+        .map(|request| request.body["nonce=".len()..].parse().unwrap())
+        .collect();
+    assert!(nonces[1] > nonces[0]);
+}
+
+#[tokio::test]
+async fn test_expiring_token_is_replaced() {
+    let rest = MockRest::start(vec![token_reply("short", 30), token_reply("fresh", 900)]).await;
+    let provider = TokenProvider::new(Auth::new("key".to_string(), SECRET.to_string()))
+        .base_url(rest.url())
+        .refresh_before(Duration::from_secs(60));
+
+    assert_eq!(provider.token().await.unwrap(), "short");
+    assert_eq!(provider.token().await.unwrap(), "fresh");
+    assert_eq!(provider.token().await.unwrap(), "fresh");
+    assert_eq!(rest.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_token_errors_are_reported() {
+    let rest = MockRest::start(vec![json!({"error": ["EAPI:Invalid key"]})]).await;
+    let provider =
+        TokenProvider::new(Auth::new("key".to_string(), SECRET.to_string())).base_url(rest.url());
+
+    match provider.token().await {
+        Err(Error::Api { method, message }) => {
+            assert_eq!(method, "GetWebSocketsToken");
+            assert_eq!(message, "EAPI:Invalid key");
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+}